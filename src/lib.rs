@@ -14,3 +14,6 @@
 // limitations under the License.
 
 pub mod s3;
+
+#[cfg(feature = "testing")]
+pub mod testing;
@@ -0,0 +1,1231 @@
+// MinIO Rust Library for Amazon S3 Compatible Cloud Storage
+// Copyright 2022 MinIO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! MinIO admin APIs to manage users, groups, canned policies and service accounts, and to
+//! inspect server state and health
+
+use crate::s3::client::Client;
+use crate::s3::creds::{credentials_error, Credentials};
+use crate::s3::error::Error;
+use crate::s3::madmin::{decrypt_data, encrypt_data};
+use crate::s3::policy::BucketPolicy;
+use crate::s3::transport::{HttpRequest, HttpResponse};
+use crate::s3::utils::{merge, to_iso8601utc, Multimap, UtcTime};
+use bytes::Bytes;
+use chrono::{DateTime, Datelike, Utc};
+use hyper::http::Method;
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+// Go encodes nil slices as null.
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountStatus {
+    #[default]
+    Enabled,
+    Disabled,
+}
+
+impl fmt::Display for AccountStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AccountStatus::Enabled => write!(f, "enabled"),
+            AccountStatus::Disabled => write!(f, "disabled"),
+        }
+    }
+}
+
+/// User as returned by user info and list users APIs
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy_name: Option<String>,
+    pub status: AccountStatus,
+    #[serde(
+        default,
+        deserialize_with = "null_as_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub member_of: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+/// Group as returned by group info API
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupInfo {
+    pub name: String,
+    pub status: AccountStatus,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub members: Vec<String>,
+    #[serde(default)]
+    pub policy: String,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GroupAddRemove<'a> {
+    group: &'a str,
+    members: &'a [&'a str],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group_status: Option<AccountStatus>,
+    is_remove: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AddServiceAccountRequest<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    policy: Option<&'a BucketPolicy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target_user: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    access_key: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    secret_key: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expiration: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CountInfo {
+    pub count: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct UsageInfo {
+    pub size: u64,
+    pub error: Option<String>,
+}
+
+/// Drive of a server
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Disk {
+    pub endpoint: String,
+    #[serde(rename = "rootDisk")]
+    pub root_disk: bool,
+    pub path: String,
+    pub healing: bool,
+    pub scanning: bool,
+    pub state: String,
+    pub uuid: String,
+    pub model: String,
+    #[serde(rename = "totalspace")]
+    pub total_space: u64,
+    #[serde(rename = "usedspace")]
+    pub used_space: u64,
+    #[serde(rename = "availspace")]
+    pub available_space: u64,
+    pub pool_index: i32,
+    pub set_index: i32,
+    pub disk_index: i32,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ErasureBackend {
+    #[serde(rename = "backendType")]
+    pub backend_type: String,
+    #[serde(rename = "onlineDisks")]
+    pub online_disks: i64,
+    #[serde(rename = "offlineDisks")]
+    pub offline_disks: i64,
+    #[serde(rename = "standardSCParity")]
+    pub standard_sc_parity: i64,
+    #[serde(rename = "rrSCParity")]
+    pub rr_sc_parity: i64,
+    #[serde(rename = "totalSets", deserialize_with = "null_as_default")]
+    pub total_sets: Vec<i64>,
+    #[serde(rename = "totalDrivesPerSet", deserialize_with = "null_as_default")]
+    pub drives_per_set: Vec<i64>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ServerProperties {
+    pub state: String,
+    pub endpoint: String,
+    pub scheme: String,
+    pub uptime: i64,
+    pub version: String,
+    #[serde(rename = "commitID")]
+    pub commit_id: String,
+    #[serde(deserialize_with = "null_as_default")]
+    pub network: HashMap<String, String>,
+    #[serde(deserialize_with = "null_as_default")]
+    pub drives: Vec<Disk>,
+    #[serde(rename = "poolNumber")]
+    pub pool_number: i32,
+}
+
+/// Cluster information as returned by server info API
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ServerInfo {
+    pub mode: String,
+    #[serde(deserialize_with = "null_as_default")]
+    pub domain: Vec<String>,
+    pub region: String,
+    #[serde(rename = "deploymentID")]
+    pub deployment_id: String,
+    pub buckets: CountInfo,
+    pub objects: CountInfo,
+    pub versions: CountInfo,
+    pub usage: UsageInfo,
+    pub backend: ErasureBackend,
+    #[serde(deserialize_with = "null_as_default")]
+    pub servers: Vec<ServerProperties>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct StorageBackend {
+    #[serde(rename = "Type")]
+    pub backend_type: i32,
+    #[serde(deserialize_with = "null_as_default")]
+    pub online_disks: HashMap<String, i64>,
+    #[serde(deserialize_with = "null_as_default")]
+    pub offline_disks: HashMap<String, i64>,
+    #[serde(rename = "StandardSCParity")]
+    pub standard_sc_parity: i64,
+    #[serde(rename = "RRSCParity")]
+    pub rr_sc_parity: i64,
+    #[serde(deserialize_with = "null_as_default")]
+    pub total_sets: Vec<i64>,
+    #[serde(deserialize_with = "null_as_default")]
+    pub drives_per_set: Vec<i64>,
+}
+
+/// Drives and backend as returned by storage info API
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct StorageInfo {
+    #[serde(deserialize_with = "null_as_default")]
+    pub disks: Vec<Disk>,
+    pub backend: StorageBackend,
+}
+
+impl StorageInfo {
+    pub fn total_space(&self) -> u64 {
+        self.disks.iter().map(|d| d.total_space).sum()
+    }
+
+    pub fn used_space(&self) -> u64 {
+        self.disks.iter().map(|d| d.used_space).sum()
+    }
+
+    pub fn available_space(&self) -> u64 {
+        self.disks.iter().map(|d| d.available_space).sum()
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BucketUsageInfo {
+    pub size: u64,
+    pub objects_count: u64,
+    pub versions_count: u64,
+    pub delete_markers_count: u64,
+    #[serde(deserialize_with = "null_as_default")]
+    pub objects_sizes_histogram: HashMap<String, u64>,
+}
+
+/// Usage computed by the data scanner as returned by data usage info API
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DataUsageInfo {
+    pub last_update: Option<String>,
+    pub objects_count: u64,
+    pub versions_count: u64,
+    pub delete_markers_count: u64,
+    pub objects_total_size: u64,
+    pub buckets_count: u64,
+    #[serde(rename = "bucketsUsageInfo", deserialize_with = "null_as_default")]
+    pub buckets_usage: HashMap<String, BucketUsageInfo>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ServiceAccountCredentials {
+    access_key: String,
+    secret_key: String,
+    #[serde(default)]
+    session_token: Option<String>,
+    #[serde(default)]
+    expiration: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AddServiceAccountResult {
+    credentials: ServiceAccountCredentials,
+}
+
+// Returns error if given name e.g. access key, group or policy name is empty.
+fn check_name(kind: &str, name: &str) -> Result<(), Error> {
+    if name.is_empty() {
+        return Err(Error::InvalidAdminArgument(format!(
+            "{} cannot be empty",
+            kind
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AddUserArgs<'a> {
+    pub extra_headers: Option<&'a Multimap>,
+    pub access_key: &'a str,
+    pub secret_key: &'a str,
+}
+
+impl<'a> AddUserArgs<'a> {
+    pub fn new(access_key: &'a str, secret_key: &'a str) -> Result<AddUserArgs<'a>, Error> {
+        check_name("access key", access_key)?;
+        check_name("secret key", secret_key)?;
+
+        Ok(AddUserArgs {
+            extra_headers: None,
+            access_key,
+            secret_key,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct UserArgs<'a> {
+    pub extra_headers: Option<&'a Multimap>,
+    pub access_key: &'a str,
+}
+
+impl<'a> UserArgs<'a> {
+    pub fn new(access_key: &'a str) -> Result<UserArgs<'a>, Error> {
+        check_name("access key", access_key)?;
+
+        Ok(UserArgs {
+            extra_headers: None,
+            access_key,
+        })
+    }
+}
+
+pub type RemoveUserArgs<'a> = UserArgs<'a>;
+
+pub type GetUserInfoArgs<'a> = UserArgs<'a>;
+
+#[derive(Clone, Debug, Default)]
+pub struct AdminArgs<'a> {
+    pub extra_headers: Option<&'a Multimap>,
+}
+
+impl<'a> AdminArgs<'a> {
+    pub fn new() -> AdminArgs<'a> {
+        AdminArgs::default()
+    }
+}
+
+pub type ListUsersArgs<'a> = AdminArgs<'a>;
+
+pub type ListGroupsArgs<'a> = AdminArgs<'a>;
+
+pub type ListCannedPoliciesArgs<'a> = AdminArgs<'a>;
+
+pub type ServerInfoArgs<'a> = AdminArgs<'a>;
+
+pub type StorageInfoArgs<'a> = AdminArgs<'a>;
+
+pub type DataUsageInfoArgs<'a> = AdminArgs<'a>;
+
+pub type HealthArgs<'a> = AdminArgs<'a>;
+
+#[derive(Clone, Debug, Default)]
+pub struct ClusterHealthArgs<'a> {
+    pub extra_headers: Option<&'a Multimap>,
+    pub maintenance: bool,
+}
+
+impl<'a> ClusterHealthArgs<'a> {
+    /// Returns arguments checking whether the cluster has write quorum; with `maintenance`,
+    /// the check tells whether this node can be taken down without losing quorum.
+    pub fn new(maintenance: bool) -> ClusterHealthArgs<'a> {
+        ClusterHealthArgs {
+            extra_headers: None,
+            maintenance,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SetUserStatusArgs<'a> {
+    pub extra_headers: Option<&'a Multimap>,
+    pub access_key: &'a str,
+    pub status: AccountStatus,
+}
+
+impl<'a> SetUserStatusArgs<'a> {
+    pub fn new(access_key: &'a str, status: AccountStatus) -> Result<SetUserStatusArgs<'a>, Error> {
+        check_name("access key", access_key)?;
+
+        Ok(SetUserStatusArgs {
+            extra_headers: None,
+            access_key,
+            status,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct UpdateGroupMembersArgs<'a> {
+    pub extra_headers: Option<&'a Multimap>,
+    pub group: &'a str,
+    pub members: &'a [&'a str],
+    pub status: Option<AccountStatus>,
+    pub remove: bool,
+}
+
+impl<'a> UpdateGroupMembersArgs<'a> {
+    /// Returns arguments adding members to the group; the group is created if it does not exist.
+    pub fn add(
+        group: &'a str,
+        members: &'a [&'a str],
+    ) -> Result<UpdateGroupMembersArgs<'a>, Error> {
+        check_name("group name", group)?;
+
+        Ok(UpdateGroupMembersArgs {
+            extra_headers: None,
+            group,
+            members,
+            status: None,
+            remove: false,
+        })
+    }
+
+    /// Returns arguments removing members from the group; an empty group is removed when no
+    /// members are given.
+    pub fn remove(
+        group: &'a str,
+        members: &'a [&'a str],
+    ) -> Result<UpdateGroupMembersArgs<'a>, Error> {
+        check_name("group name", group)?;
+
+        Ok(UpdateGroupMembersArgs {
+            extra_headers: None,
+            group,
+            members,
+            status: None,
+            remove: true,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct GroupArgs<'a> {
+    pub extra_headers: Option<&'a Multimap>,
+    pub group: &'a str,
+}
+
+impl<'a> GroupArgs<'a> {
+    pub fn new(group: &'a str) -> Result<GroupArgs<'a>, Error> {
+        check_name("group name", group)?;
+
+        Ok(GroupArgs {
+            extra_headers: None,
+            group,
+        })
+    }
+}
+
+pub type GetGroupInfoArgs<'a> = GroupArgs<'a>;
+
+#[derive(Clone, Debug, Default)]
+pub struct SetGroupStatusArgs<'a> {
+    pub extra_headers: Option<&'a Multimap>,
+    pub group: &'a str,
+    pub status: AccountStatus,
+}
+
+impl<'a> SetGroupStatusArgs<'a> {
+    pub fn new(group: &'a str, status: AccountStatus) -> Result<SetGroupStatusArgs<'a>, Error> {
+        check_name("group name", group)?;
+
+        Ok(SetGroupStatusArgs {
+            extra_headers: None,
+            group,
+            status,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AddCannedPolicyArgs<'a> {
+    pub extra_headers: Option<&'a Multimap>,
+    pub name: &'a str,
+    pub policy: &'a BucketPolicy,
+}
+
+impl<'a> AddCannedPolicyArgs<'a> {
+    /// Returns arguments adding IAM policy, which shares grammar of bucket policy except that
+    /// statements have no principal.
+    pub fn new(name: &'a str, policy: &'a BucketPolicy) -> Result<AddCannedPolicyArgs<'a>, Error> {
+        check_name("policy name", name)?;
+
+        Ok(AddCannedPolicyArgs {
+            extra_headers: None,
+            name,
+            policy,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RemoveCannedPolicyArgs<'a> {
+    pub extra_headers: Option<&'a Multimap>,
+    pub name: &'a str,
+}
+
+impl<'a> RemoveCannedPolicyArgs<'a> {
+    pub fn new(name: &'a str) -> Result<RemoveCannedPolicyArgs<'a>, Error> {
+        check_name("policy name", name)?;
+
+        Ok(RemoveCannedPolicyArgs {
+            extra_headers: None,
+            name,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AttachPolicyArgs<'a> {
+    pub extra_headers: Option<&'a Multimap>,
+    pub policy_name: &'a str,
+    pub user_or_group: &'a str,
+    pub is_group: bool,
+}
+
+impl<'a> AttachPolicyArgs<'a> {
+    /// Returns arguments setting comma separated policy names of given user.
+    pub fn user(policy_name: &'a str, user: &'a str) -> Result<AttachPolicyArgs<'a>, Error> {
+        check_name("policy name", policy_name)?;
+        check_name("user name", user)?;
+
+        Ok(AttachPolicyArgs {
+            extra_headers: None,
+            policy_name,
+            user_or_group: user,
+            is_group: false,
+        })
+    }
+
+    /// Returns arguments setting comma separated policy names of given group.
+    pub fn group(policy_name: &'a str, group: &'a str) -> Result<AttachPolicyArgs<'a>, Error> {
+        check_name("policy name", policy_name)?;
+        check_name("group name", group)?;
+
+        Ok(AttachPolicyArgs {
+            extra_headers: None,
+            policy_name,
+            user_or_group: group,
+            is_group: true,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AddServiceAccountArgs<'a> {
+    pub extra_headers: Option<&'a Multimap>,
+    pub target_user: Option<&'a str>,
+    pub access_key: Option<&'a str>,
+    pub secret_key: Option<&'a str>,
+    pub policy: Option<&'a BucketPolicy>,
+    pub name: Option<&'a str>,
+    pub description: Option<&'a str>,
+    pub expiration: Option<UtcTime>,
+}
+
+impl<'a> AddServiceAccountArgs<'a> {
+    /// Returns arguments creating service account of the requesting user with generated
+    /// credentials.
+    pub fn new() -> AddServiceAccountArgs<'a> {
+        AddServiceAccountArgs::default()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DeleteServiceAccountArgs<'a> {
+    pub extra_headers: Option<&'a Multimap>,
+    pub access_key: &'a str,
+}
+
+impl<'a> DeleteServiceAccountArgs<'a> {
+    pub fn new(access_key: &'a str) -> Result<DeleteServiceAccountArgs<'a>, Error> {
+        check_name("access key", access_key)?;
+
+        Ok(DeleteServiceAccountArgs {
+            extra_headers: None,
+            access_key,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AdminResponse {
+    pub headers: HeaderMap,
+}
+
+pub type AddUserResponse = AdminResponse;
+
+pub type RemoveUserResponse = AdminResponse;
+
+pub type SetUserStatusResponse = AdminResponse;
+
+pub type UpdateGroupMembersResponse = AdminResponse;
+
+pub type SetGroupStatusResponse = AdminResponse;
+
+pub type AddCannedPolicyResponse = AdminResponse;
+
+pub type RemoveCannedPolicyResponse = AdminResponse;
+
+pub type AttachPolicyResponse = AdminResponse;
+
+pub type DeleteServiceAccountResponse = AdminResponse;
+
+#[derive(Clone, Debug)]
+pub struct GetUserInfoResponse {
+    pub headers: HeaderMap,
+    pub access_key: String,
+    pub info: UserInfo,
+}
+
+#[derive(Clone, Debug)]
+pub struct ListUsersResponse {
+    pub headers: HeaderMap,
+    pub users: HashMap<String, UserInfo>,
+}
+
+#[derive(Clone, Debug)]
+pub struct GetGroupInfoResponse {
+    pub headers: HeaderMap,
+    pub info: GroupInfo,
+}
+
+#[derive(Clone, Debug)]
+pub struct ListGroupsResponse {
+    pub headers: HeaderMap,
+    pub groups: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ListCannedPoliciesResponse {
+    pub headers: HeaderMap,
+    pub policies: HashMap<String, Value>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ServerInfoResponse {
+    pub headers: HeaderMap,
+    pub info: ServerInfo,
+}
+
+#[derive(Clone, Debug)]
+pub struct StorageInfoResponse {
+    pub headers: HeaderMap,
+    pub info: StorageInfo,
+}
+
+#[derive(Clone, Debug)]
+pub struct DataUsageInfoResponse {
+    pub headers: HeaderMap,
+    pub info: DataUsageInfo,
+}
+
+#[derive(Clone, Debug)]
+pub struct HealthResponse {
+    pub headers: HeaderMap,
+    pub healthy: bool,
+    /// Write quorum reported by cluster health check.
+    pub write_quorum: Option<u32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct AddServiceAccountResponse {
+    pub headers: HeaderMap,
+    pub credentials: Credentials,
+}
+
+impl Client {
+    // Health checks are unauthenticated and report unhealthy state by status code, so the
+    // response is returned as is.
+    async fn execute_health(
+        &self,
+        path: &str,
+        extra_headers: Option<&Multimap>,
+        query_params: &Multimap,
+    ) -> Result<HealthResponse, Error> {
+        if self.base_url.aws_host {
+            return Err(Error::UnsupportedApi(String::from("HealthCheck")));
+        }
+
+        let url = self
+            .base_url
+            .build_path_url(&format!("/minio/health/{}", path), query_params);
+
+        let mut headers = Multimap::new();
+        if let Some(v) = extra_headers {
+            merge(&mut headers, v);
+        }
+        headers.insert(String::from("Host"), url.host_header_value());
+        headers.insert(String::from("User-Agent"), self.user_agent().to_string());
+
+        let resp = self
+            .send(HttpRequest {
+                method: Method::GET,
+                url,
+                headers,
+                body: Bytes::new(),
+                chunk_signer: None,
+            })
+            .await?;
+
+        let write_quorum = match resp.headers().get("x-minio-write-quorum") {
+            Some(v) => Some(v.to_str()?.parse()?),
+            None => None,
+        };
+
+        Ok(HealthResponse {
+            headers: resp.headers().clone(),
+            healthy: resp.status().is_success(),
+            write_quorum,
+        })
+    }
+
+    // Secret key of the requesting user is the password of encrypted admin payloads.
+    async fn admin_secret_key(&self) -> Result<String, Error> {
+        match &self.provider {
+            Some(p) => Ok(p.fetch().await.map_err(credentials_error)?.secret_key),
+            None => Err(Error::CredentialsError(String::from(
+                "admin API requires credentials",
+            ))),
+        }
+    }
+
+    async fn encrypt_admin_data(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        encrypt_data(&self.admin_secret_key().await?, data)
+    }
+
+    async fn decrypt_admin_response(&self, resp: HttpResponse) -> Result<Vec<u8>, Error> {
+        let body = resp.bytes().await?;
+        decrypt_data(&self.admin_secret_key().await?, &body)
+    }
+
+    pub async fn add_user(&self, args: &AddUserArgs<'_>) -> Result<AddUserResponse, Error> {
+        let mut query_params = Multimap::new();
+        query_params.insert(String::from("accessKey"), args.access_key.to_string());
+
+        let data = serde_json::to_vec(&UserInfo {
+            secret_key: Some(args.secret_key.to_string()),
+            status: AccountStatus::Enabled,
+            ..Default::default()
+        })?;
+        let data = self.encrypt_admin_data(&data).await?;
+
+        let resp = self
+            .execute_admin(
+                Method::PUT,
+                "add-user",
+                args.extra_headers,
+                &query_params,
+                Some(Bytes::from(data)),
+            )
+            .await?;
+
+        Ok(AddUserResponse {
+            headers: resp.headers().clone(),
+        })
+    }
+
+    pub async fn remove_user(
+        &self,
+        args: &RemoveUserArgs<'_>,
+    ) -> Result<RemoveUserResponse, Error> {
+        let mut query_params = Multimap::new();
+        query_params.insert(String::from("accessKey"), args.access_key.to_string());
+
+        let resp = self
+            .execute_admin(
+                Method::DELETE,
+                "remove-user",
+                args.extra_headers,
+                &query_params,
+                None,
+            )
+            .await?;
+
+        Ok(RemoveUserResponse {
+            headers: resp.headers().clone(),
+        })
+    }
+
+    pub async fn get_user_info(
+        &self,
+        args: &GetUserInfoArgs<'_>,
+    ) -> Result<GetUserInfoResponse, Error> {
+        let mut query_params = Multimap::new();
+        query_params.insert(String::from("accessKey"), args.access_key.to_string());
+
+        let resp = self
+            .execute_admin(
+                Method::GET,
+                "user-info",
+                args.extra_headers,
+                &query_params,
+                None,
+            )
+            .await?;
+
+        let headers = resp.headers().clone();
+        let body = resp.bytes().await?;
+
+        Ok(GetUserInfoResponse {
+            headers,
+            access_key: args.access_key.to_string(),
+            info: serde_json::from_slice(&body)?,
+        })
+    }
+
+    pub async fn list_users(&self, args: &ListUsersArgs<'_>) -> Result<ListUsersResponse, Error> {
+        let resp = self
+            .execute_admin(
+                Method::GET,
+                "list-users",
+                args.extra_headers,
+                &Multimap::new(),
+                None,
+            )
+            .await?;
+
+        let headers = resp.headers().clone();
+        let body = self.decrypt_admin_response(resp).await?;
+
+        Ok(ListUsersResponse {
+            headers,
+            users: serde_json::from_slice(&body)?,
+        })
+    }
+
+    pub async fn set_user_status(
+        &self,
+        args: &SetUserStatusArgs<'_>,
+    ) -> Result<SetUserStatusResponse, Error> {
+        let mut query_params = Multimap::new();
+        query_params.insert(String::from("accessKey"), args.access_key.to_string());
+        query_params.insert(String::from("status"), args.status.to_string());
+
+        let resp = self
+            .execute_admin(
+                Method::PUT,
+                "set-user-status",
+                args.extra_headers,
+                &query_params,
+                None,
+            )
+            .await?;
+
+        Ok(SetUserStatusResponse {
+            headers: resp.headers().clone(),
+        })
+    }
+
+    pub async fn update_group_members(
+        &self,
+        args: &UpdateGroupMembersArgs<'_>,
+    ) -> Result<UpdateGroupMembersResponse, Error> {
+        let data = serde_json::to_vec(&GroupAddRemove {
+            group: args.group,
+            members: args.members,
+            group_status: args.status,
+            is_remove: args.remove,
+        })?;
+
+        let resp = self
+            .execute_admin(
+                Method::PUT,
+                "update-group-members",
+                args.extra_headers,
+                &Multimap::new(),
+                Some(Bytes::from(data)),
+            )
+            .await?;
+
+        Ok(UpdateGroupMembersResponse {
+            headers: resp.headers().clone(),
+        })
+    }
+
+    pub async fn get_group_info(
+        &self,
+        args: &GetGroupInfoArgs<'_>,
+    ) -> Result<GetGroupInfoResponse, Error> {
+        let mut query_params = Multimap::new();
+        query_params.insert(String::from("group"), args.group.to_string());
+
+        let resp = self
+            .execute_admin(
+                Method::GET,
+                "group",
+                args.extra_headers,
+                &query_params,
+                None,
+            )
+            .await?;
+
+        let headers = resp.headers().clone();
+        let body = resp.bytes().await?;
+
+        Ok(GetGroupInfoResponse {
+            headers,
+            info: serde_json::from_slice(&body)?,
+        })
+    }
+
+    pub async fn list_groups(
+        &self,
+        args: &ListGroupsArgs<'_>,
+    ) -> Result<ListGroupsResponse, Error> {
+        let resp = self
+            .execute_admin(
+                Method::GET,
+                "groups",
+                args.extra_headers,
+                &Multimap::new(),
+                None,
+            )
+            .await?;
+
+        let headers = resp.headers().clone();
+        let body = resp.bytes().await?;
+        let groups: Option<Vec<String>> = serde_json::from_slice(&body)?;
+
+        Ok(ListGroupsResponse {
+            headers,
+            groups: groups.unwrap_or_default(),
+        })
+    }
+
+    pub async fn set_group_status(
+        &self,
+        args: &SetGroupStatusArgs<'_>,
+    ) -> Result<SetGroupStatusResponse, Error> {
+        let mut query_params = Multimap::new();
+        query_params.insert(String::from("group"), args.group.to_string());
+        query_params.insert(String::from("status"), args.status.to_string());
+
+        let resp = self
+            .execute_admin(
+                Method::PUT,
+                "set-group-status",
+                args.extra_headers,
+                &query_params,
+                None,
+            )
+            .await?;
+
+        Ok(SetGroupStatusResponse {
+            headers: resp.headers().clone(),
+        })
+    }
+
+    pub async fn add_canned_policy(
+        &self,
+        args: &AddCannedPolicyArgs<'_>,
+    ) -> Result<AddCannedPolicyResponse, Error> {
+        let mut query_params = Multimap::new();
+        query_params.insert(String::from("name"), args.name.to_string());
+
+        let data = serde_json::to_vec(args.policy)?;
+
+        let resp = self
+            .execute_admin(
+                Method::PUT,
+                "add-canned-policy",
+                args.extra_headers,
+                &query_params,
+                Some(Bytes::from(data)),
+            )
+            .await?;
+
+        Ok(AddCannedPolicyResponse {
+            headers: resp.headers().clone(),
+        })
+    }
+
+    pub async fn remove_canned_policy(
+        &self,
+        args: &RemoveCannedPolicyArgs<'_>,
+    ) -> Result<RemoveCannedPolicyResponse, Error> {
+        let mut query_params = Multimap::new();
+        query_params.insert(String::from("name"), args.name.to_string());
+
+        let resp = self
+            .execute_admin(
+                Method::DELETE,
+                "remove-canned-policy",
+                args.extra_headers,
+                &query_params,
+                None,
+            )
+            .await?;
+
+        Ok(RemoveCannedPolicyResponse {
+            headers: resp.headers().clone(),
+        })
+    }
+
+    pub async fn list_canned_policies(
+        &self,
+        args: &ListCannedPoliciesArgs<'_>,
+    ) -> Result<ListCannedPoliciesResponse, Error> {
+        let resp = self
+            .execute_admin(
+                Method::GET,
+                "list-canned-policies",
+                args.extra_headers,
+                &Multimap::new(),
+                None,
+            )
+            .await?;
+
+        let headers = resp.headers().clone();
+        let body = resp.bytes().await?;
+
+        Ok(ListCannedPoliciesResponse {
+            headers,
+            policies: serde_json::from_slice(&body)?,
+        })
+    }
+
+    /// Replaces policies attached to a user or group by given comma separated policy names.
+    pub async fn attach_policy(
+        &self,
+        args: &AttachPolicyArgs<'_>,
+    ) -> Result<AttachPolicyResponse, Error> {
+        let mut query_params = Multimap::new();
+        query_params.insert(String::from("policyName"), args.policy_name.to_string());
+        query_params.insert(String::from("userOrGroup"), args.user_or_group.to_string());
+        query_params.insert(String::from("isGroup"), args.is_group.to_string());
+
+        let resp = self
+            .execute_admin(
+                Method::PUT,
+                "set-user-or-group-policy",
+                args.extra_headers,
+                &query_params,
+                None,
+            )
+            .await?;
+
+        Ok(AttachPolicyResponse {
+            headers: resp.headers().clone(),
+        })
+    }
+
+    pub async fn add_service_account(
+        &self,
+        args: &AddServiceAccountArgs<'_>,
+    ) -> Result<AddServiceAccountResponse, Error> {
+        let data = serde_json::to_vec(&AddServiceAccountRequest {
+            policy: args.policy,
+            target_user: args.target_user,
+            access_key: args.access_key,
+            secret_key: args.secret_key,
+            name: args.name,
+            description: args.description,
+            expiration: args.expiration.map(to_iso8601utc),
+        })?;
+        let data = self.encrypt_admin_data(&data).await?;
+
+        let resp = self
+            .execute_admin(
+                Method::PUT,
+                "add-service-account",
+                args.extra_headers,
+                &Multimap::new(),
+                Some(Bytes::from(data)),
+            )
+            .await?;
+
+        let headers = resp.headers().clone();
+        let body = self.decrypt_admin_response(resp).await?;
+        let result: AddServiceAccountResult = serde_json::from_slice(&body)?;
+        let creds = result.credentials;
+
+        // Go encodes missing expiration as zero time.
+        let expiration = match creds.expiration {
+            Some(v) => Some(DateTime::parse_from_rfc3339(&v)?.with_timezone(&Utc)),
+            None => None,
+        };
+
+        Ok(AddServiceAccountResponse {
+            headers,
+            credentials: Credentials {
+                access_key: creds.access_key,
+                secret_key: creds.secret_key,
+                session_token: creds.session_token.filter(|v| !v.is_empty()),
+                expiration: expiration.filter(|v| v.year() > 1),
+            },
+        })
+    }
+
+    pub async fn delete_service_account(
+        &self,
+        args: &DeleteServiceAccountArgs<'_>,
+    ) -> Result<DeleteServiceAccountResponse, Error> {
+        let mut query_params = Multimap::new();
+        query_params.insert(String::from("accessKey"), args.access_key.to_string());
+
+        let resp = self
+            .execute_admin(
+                Method::DELETE,
+                "delete-service-account",
+                args.extra_headers,
+                &query_params,
+                None,
+            )
+            .await?;
+
+        Ok(DeleteServiceAccountResponse {
+            headers: resp.headers().clone(),
+        })
+    }
+
+    pub async fn server_info(
+        &self,
+        args: &ServerInfoArgs<'_>,
+    ) -> Result<ServerInfoResponse, Error> {
+        let resp = self
+            .execute_admin(
+                Method::GET,
+                "info",
+                args.extra_headers,
+                &Multimap::new(),
+                None,
+            )
+            .await?;
+
+        let headers = resp.headers().clone();
+        let body = resp.bytes().await?;
+
+        Ok(ServerInfoResponse {
+            headers,
+            info: serde_json::from_slice(&body)?,
+        })
+    }
+
+    pub async fn storage_info(
+        &self,
+        args: &StorageInfoArgs<'_>,
+    ) -> Result<StorageInfoResponse, Error> {
+        let resp = self
+            .execute_admin(
+                Method::GET,
+                "storageinfo",
+                args.extra_headers,
+                &Multimap::new(),
+                None,
+            )
+            .await?;
+
+        let headers = resp.headers().clone();
+        let body = resp.bytes().await?;
+
+        Ok(StorageInfoResponse {
+            headers,
+            info: serde_json::from_slice(&body)?,
+        })
+    }
+
+    pub async fn data_usage_info(
+        &self,
+        args: &DataUsageInfoArgs<'_>,
+    ) -> Result<DataUsageInfoResponse, Error> {
+        let resp = self
+            .execute_admin(
+                Method::GET,
+                "datausageinfo",
+                args.extra_headers,
+                &Multimap::new(),
+                None,
+            )
+            .await?;
+
+        let headers = resp.headers().clone();
+        let body = resp.bytes().await?;
+
+        Ok(DataUsageInfoResponse {
+            headers,
+            info: serde_json::from_slice(&body)?,
+        })
+    }
+
+    /// Checks whether the server is up.
+    pub async fn health_live(&self, args: &HealthArgs<'_>) -> Result<HealthResponse, Error> {
+        self.execute_health("live", args.extra_headers, &Multimap::new())
+            .await
+    }
+
+    /// Checks whether the server is ready to serve requests.
+    pub async fn health_ready(&self, args: &HealthArgs<'_>) -> Result<HealthResponse, Error> {
+        self.execute_health("ready", args.extra_headers, &Multimap::new())
+            .await
+    }
+
+    /// Checks whether the cluster has write quorum.
+    pub async fn health_cluster(
+        &self,
+        args: &ClusterHealthArgs<'_>,
+    ) -> Result<HealthResponse, Error> {
+        let mut query_params = Multimap::new();
+        if args.maintenance {
+            query_params.insert(String::from("maintenance"), String::from("true"));
+        }
+
+        self.execute_health("cluster", args.extra_headers, &query_params)
+            .await
+    }
+}
@@ -13,7 +13,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::s3::checksum::ChecksumAlgorithm;
 use crate::s3::error::Error;
+use crate::s3::policy::{AccessLevel, BucketPolicy};
 use crate::s3::signer::post_presign_v4;
 use crate::s3::sse::{Sse, SseCustomerKey};
 use crate::s3::types::{
@@ -23,19 +25,25 @@ use crate::s3::types::{
 };
 use crate::s3::utils::{
     b64encode, check_bucket_name, merge, to_amz_date, to_http_header_value, to_iso8601utc,
-    to_signer_date, urlencode, utc_now, Multimap, UtcTime,
+    to_signer_date, urlencode, urlencode_path, utc_now, Multimap, UtcTime,
 };
+use bytes::Bytes;
 use derivative::Derivative;
+use futures_core::Stream;
+use futures_util::StreamExt;
 use hyper::http::Method;
 use serde_json::json;
 use serde_json::Value;
 use std::collections::HashMap;
+use tokio::io::{AsyncRead, AsyncReadExt};
 
 pub const MIN_PART_SIZE: usize = 5_242_880; // 5 MiB
 pub const MAX_PART_SIZE: usize = 5_368_709_120; // 5 GiB
 pub const MAX_OBJECT_SIZE: usize = 5_497_558_138_880; // 5 TiB
 pub const MAX_MULTIPART_COUNT: u16 = 10_000;
 pub const DEFAULT_EXPIRY_SECONDS: u32 = 604_800; // 7 days
+pub const DEFAULT_PARALLEL_UPLOADS: usize = 1; // peak memory is this many part buffers
+pub const DEFAULT_DOWNLOAD_PART_SIZE: usize = 16_777_216; // 16 MiB
 
 fn object_write_args_headers(
     extra_headers: Option<&Multimap>,
@@ -124,7 +132,7 @@ fn calc_part_info(
         return Ok((part_size.unwrap(), -1));
     }
 
-    let mut psize = 0_usize;
+    let mut psize = part_size.unwrap_or_default();
     if part_size.is_none() {
         psize = (object_size.unwrap() as f64 / MAX_MULTIPART_COUNT as f64).ceil() as usize;
         psize = MIN_PART_SIZE * (psize as f64 / MIN_PART_SIZE as f64).ceil() as usize;
@@ -341,6 +349,7 @@ pub struct CompleteMultipartUploadArgs<'a> {
     pub object: &'a str,
     pub upload_id: &'a str,
     pub parts: &'a Vec<Part>,
+    pub checksum: Option<ChecksumAlgorithm>, // verifies composite checksum of parts
 }
 
 impl<'a> CompleteMultipartUploadArgs<'a> {
@@ -376,6 +385,7 @@ impl<'a> CompleteMultipartUploadArgs<'a> {
             object: object_name,
             upload_id,
             parts,
+            checksum: None,
         })
     }
 }
@@ -388,6 +398,7 @@ pub struct CreateMultipartUploadArgs<'a> {
     pub bucket: &'a str,
     pub object: &'a str,
     pub headers: Option<&'a Multimap>,
+    pub checksum: Option<ChecksumAlgorithm>,
 }
 
 impl<'a> CreateMultipartUploadArgs<'a> {
@@ -410,6 +421,85 @@ impl<'a> CreateMultipartUploadArgs<'a> {
             bucket: bucket_name,
             object: object_name,
             headers: None,
+            checksum: None,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ListPartsArgs<'a> {
+    pub extra_headers: Option<&'a Multimap>,
+    pub extra_query_params: Option<&'a Multimap>,
+    pub region: Option<&'a str>,
+    pub bucket: &'a str,
+    pub object: &'a str,
+    pub upload_id: &'a str,
+    pub max_parts: Option<u16>,
+    pub part_number_marker: Option<u16>,
+}
+
+impl<'a> ListPartsArgs<'a> {
+    pub fn new(
+        bucket_name: &'a str,
+        object_name: &'a str,
+        upload_id: &'a str,
+    ) -> Result<ListPartsArgs<'a>, Error> {
+        check_bucket_name(bucket_name, true)?;
+
+        if object_name.is_empty() {
+            return Err(Error::InvalidObjectName(String::from(
+                "object name cannot be empty",
+            )));
+        }
+
+        if upload_id.is_empty() {
+            return Err(Error::InvalidUploadId(String::from(
+                "upload ID cannot be empty",
+            )));
+        }
+
+        Ok(ListPartsArgs {
+            extra_headers: None,
+            extra_query_params: None,
+            region: None,
+            bucket: bucket_name,
+            object: object_name,
+            upload_id,
+            max_parts: None,
+            part_number_marker: None,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ListMultipartUploadsArgs<'a> {
+    pub extra_headers: Option<&'a Multimap>,
+    pub extra_query_params: Option<&'a Multimap>,
+    pub region: Option<&'a str>,
+    pub bucket: &'a str,
+    pub delimiter: Option<&'a str>,
+    pub encoding_type: Option<&'a str>,
+    pub max_uploads: Option<u16>,
+    pub prefix: Option<&'a str>,
+    pub key_marker: Option<String>,
+    pub upload_id_marker: Option<String>,
+}
+
+impl<'a> ListMultipartUploadsArgs<'a> {
+    pub fn new(bucket_name: &'a str) -> Result<ListMultipartUploadsArgs<'a>, Error> {
+        check_bucket_name(bucket_name, true)?;
+
+        Ok(ListMultipartUploadsArgs {
+            extra_headers: None,
+            extra_query_params: None,
+            region: None,
+            bucket: bucket_name,
+            delimiter: None,
+            encoding_type: None,
+            max_uploads: None,
+            prefix: None,
+            key_marker: None,
+            upload_id_marker: None,
         })
     }
 }
@@ -427,15 +517,16 @@ pub struct PutObjectApiArgs<'a> {
     pub tags: Option<&'a HashMap<String, String>>,
     pub retention: Option<&'a Retention>,
     pub legal_hold: bool,
-    pub data: &'a [u8],
+    pub data: Bytes,
     pub query_params: Option<&'a Multimap>,
+    pub checksum: Option<ChecksumAlgorithm>,
 }
 
 impl<'a> PutObjectApiArgs<'a> {
     pub fn new(
         bucket_name: &'a str,
         object_name: &'a str,
-        data: &'a [u8],
+        data: Bytes,
     ) -> Result<PutObjectApiArgs<'a>, Error> {
         check_bucket_name(bucket_name, true)?;
 
@@ -459,6 +550,7 @@ impl<'a> PutObjectApiArgs<'a> {
             legal_hold: false,
             data,
             query_params: None,
+            checksum: None,
         })
     }
 
@@ -490,7 +582,8 @@ pub struct UploadPartArgs<'a> {
     pub legal_hold: bool,
     pub upload_id: &'a str,
     pub part_number: u16,
-    pub data: &'a [u8],
+    pub data: Bytes,
+    pub checksum: Option<ChecksumAlgorithm>,
 }
 
 impl<'a> UploadPartArgs<'a> {
@@ -499,7 +592,7 @@ impl<'a> UploadPartArgs<'a> {
         object_name: &'a str,
         upload_id: &'a str,
         part_number: u16,
-        data: &'a [u8],
+        data: Bytes,
     ) -> Result<UploadPartArgs<'a>, Error> {
         check_bucket_name(bucket_name, true)?;
 
@@ -536,6 +629,7 @@ impl<'a> UploadPartArgs<'a> {
             upload_id,
             part_number,
             data,
+            checksum: None,
         })
     }
 
@@ -552,6 +646,39 @@ impl<'a> UploadPartArgs<'a> {
     }
 }
 
+/// Source of object data for [PutObjectArgs]
+pub enum ObjectReader<'a> {
+    Read(&'a mut (dyn std::io::Read + Send)),
+    AsyncRead(&'a mut (dyn AsyncRead + Unpin + Send)),
+    // Bytes left over from the last chunk yielded by the stream.
+    Stream(
+        &'a mut (dyn Stream<Item = Result<Bytes, std::io::Error>> + Unpin + Send),
+        Bytes,
+    ),
+}
+
+impl<'a> ObjectReader<'a> {
+    /// Reads up to buf.len() bytes and returns 0 at end of data, like [std::io::Read::read]
+    pub async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        match self {
+            ObjectReader::Read(r) => Ok(r.read(buf)?),
+            ObjectReader::AsyncRead(r) => Ok(r.read(buf).await?),
+            ObjectReader::Stream(s, pending) => {
+                while pending.is_empty() {
+                    match s.next().await {
+                        Some(v) => *pending = v?,
+                        None => return Ok(0),
+                    }
+                }
+
+                let n = std::cmp::min(buf.len(), pending.len());
+                buf[..n].copy_from_slice(&pending.split_to(n));
+                Ok(n)
+            }
+        }
+    }
+}
+
 pub struct PutObjectArgs<'a> {
     pub extra_headers: Option<&'a Multimap>,
     pub extra_query_params: Option<&'a Multimap>,
@@ -568,14 +695,66 @@ pub struct PutObjectArgs<'a> {
     pub part_size: usize,
     pub part_count: i16,
     pub content_type: &'a str,
-    pub stream: &'a mut dyn std::io::Read,
+    pub parallel_uploads: usize, // parts uploaded concurrently, each buffered in memory
+    pub max_buffer_size: Option<usize>, // caps memory of in-flight parts; at least one part
+    pub upload_id: Option<&'a str>, // resumes this multipart upload
+    pub checksum: Option<ChecksumAlgorithm>, // sent with each part if multipart
+    pub stream: ObjectReader<'a>,
 }
 
 impl<'a> PutObjectArgs<'a> {
     pub fn new(
         bucket_name: &'a str,
         object_name: &'a str,
-        stream: &'a mut dyn std::io::Read,
+        stream: &'a mut (dyn std::io::Read + Send),
+        object_size: Option<usize>,
+        part_size: Option<usize>,
+    ) -> Result<PutObjectArgs<'a>, Error> {
+        PutObjectArgs::from_reader(
+            bucket_name,
+            object_name,
+            ObjectReader::Read(stream),
+            object_size,
+            part_size,
+        )
+    }
+
+    pub fn from_async_read(
+        bucket_name: &'a str,
+        object_name: &'a str,
+        reader: &'a mut (dyn AsyncRead + Unpin + Send),
+        object_size: Option<usize>,
+        part_size: Option<usize>,
+    ) -> Result<PutObjectArgs<'a>, Error> {
+        PutObjectArgs::from_reader(
+            bucket_name,
+            object_name,
+            ObjectReader::AsyncRead(reader),
+            object_size,
+            part_size,
+        )
+    }
+
+    pub fn from_stream(
+        bucket_name: &'a str,
+        object_name: &'a str,
+        stream: &'a mut (dyn Stream<Item = Result<Bytes, std::io::Error>> + Unpin + Send),
+        object_size: Option<usize>,
+        part_size: Option<usize>,
+    ) -> Result<PutObjectArgs<'a>, Error> {
+        PutObjectArgs::from_reader(
+            bucket_name,
+            object_name,
+            ObjectReader::Stream(stream, Bytes::new()),
+            object_size,
+            part_size,
+        )
+    }
+
+    pub fn from_reader(
+        bucket_name: &'a str,
+        object_name: &'a str,
+        stream: ObjectReader<'a>,
         object_size: Option<usize>,
         part_size: Option<usize>,
     ) -> Result<PutObjectArgs<'a>, Error> {
@@ -605,6 +784,10 @@ impl<'a> PutObjectArgs<'a> {
             part_size: psize,
             part_count,
             content_type: "application/octet-stream",
+            parallel_uploads: DEFAULT_PARALLEL_UPLOADS,
+            max_buffer_size: None,
+            upload_id: None,
+            checksum: None,
             stream,
         })
     }
@@ -620,6 +803,14 @@ impl<'a> PutObjectArgs<'a> {
             self.legal_hold,
         )
     }
+
+    // Size of buffer holding one part; one extra byte detects end of data of unknown size.
+    pub(crate) fn part_buffer_size(&self) -> usize {
+        match self.part_count > 0 {
+            true => self.part_size,
+            false => self.part_size + 1,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -637,6 +828,7 @@ pub struct ObjectConditionalReadArgs<'a> {
     pub not_match_etag: Option<&'a str>,
     pub modified_since: Option<UtcTime>,
     pub unmodified_since: Option<UtcTime>,
+    pub checksum_mode: bool, // requests checksum; object data is verified by get_object
 }
 
 impl<'a> ObjectConditionalReadArgs<'a> {
@@ -666,6 +858,7 @@ impl<'a> ObjectConditionalReadArgs<'a> {
             not_match_etag: None,
             modified_since: None,
             unmodified_since: None,
+            checksum_mode: false,
         })
     }
 
@@ -716,6 +909,10 @@ impl<'a> ObjectConditionalReadArgs<'a> {
             merge(&mut headers, &v.headers());
         }
 
+        if self.checksum_mode {
+            headers.insert(String::from("x-amz-checksum-mode"), String::from("ENABLED"));
+        }
+
         headers
     }
 
@@ -725,7 +922,7 @@ impl<'a> ObjectConditionalReadArgs<'a> {
         let mut copy_source = String::from("/");
         copy_source.push_str(self.bucket);
         copy_source.push('/');
-        copy_source.push_str(self.object);
+        copy_source.push_str(&urlencode_path(self.object));
         if let Some(v) = self.version_id {
             copy_source.push_str("?versionId=");
             copy_source.push_str(&urlencode(v));
@@ -985,6 +1182,79 @@ impl<'a> ListObjectsArgs<'a> {
     }
 }
 
+pub struct ListObjectsStreamArgs<'a> {
+    pub extra_headers: Option<&'a Multimap>,
+    pub extra_query_params: Option<&'a Multimap>,
+    pub region: Option<&'a str>,
+    pub bucket: &'a str,
+    pub delimiter: Option<&'a str>,
+    pub use_url_encoding_type: bool,
+    pub marker: Option<&'a str>,      // only for ListObjectsV1.
+    pub start_after: Option<&'a str>, // only for ListObjectsV2.
+    pub key_marker: Option<&'a str>,  // only for GetObjectVersions.
+    pub max_keys: Option<u16>,
+    pub prefix: Option<&'a str>,
+    pub continuation_token: Option<&'a str>, // only for ListObjectsV2.
+    pub fetch_owner: bool,                   // only for ListObjectsV2.
+    pub version_id_marker: Option<&'a str>,  // only for GetObjectVersions.
+    pub include_user_metadata: bool,         // MinIO extension for ListObjectsV2.
+    pub recursive: bool,
+    pub use_api_v1: bool,
+    pub include_versions: bool,
+}
+
+impl<'a> ListObjectsStreamArgs<'a> {
+    pub fn new(bucket_name: &'a str) -> Result<ListObjectsStreamArgs<'a>, Error> {
+        check_bucket_name(bucket_name, true)?;
+
+        Ok(ListObjectsStreamArgs {
+            extra_headers: None,
+            extra_query_params: None,
+            region: None,
+            bucket: bucket_name,
+            delimiter: None,
+            use_url_encoding_type: true,
+            marker: None,
+            start_after: None,
+            key_marker: None,
+            max_keys: None,
+            prefix: None,
+            continuation_token: None,
+            fetch_owner: false,
+            version_id_marker: None,
+            include_user_metadata: false,
+            recursive: false,
+            use_api_v1: false,
+            include_versions: false,
+        })
+    }
+}
+
+impl<'a> From<&ListObjectsArgs<'a>> for ListObjectsStreamArgs<'a> {
+    fn from(args: &ListObjectsArgs<'a>) -> Self {
+        ListObjectsStreamArgs {
+            extra_headers: args.extra_headers,
+            extra_query_params: args.extra_query_params,
+            region: args.region,
+            bucket: args.bucket,
+            delimiter: args.delimiter,
+            use_url_encoding_type: args.use_url_encoding_type,
+            marker: args.marker,
+            start_after: args.start_after,
+            key_marker: args.key_marker,
+            max_keys: args.max_keys,
+            prefix: args.prefix,
+            continuation_token: args.continuation_token,
+            fetch_owner: args.fetch_owner,
+            version_id_marker: args.version_id_marker,
+            include_user_metadata: args.include_user_metadata,
+            recursive: args.recursive,
+            use_api_v1: args.use_api_v1,
+            include_versions: args.include_versions,
+        }
+    }
+}
+
 pub struct SelectObjectContentArgs<'a> {
     pub extra_headers: Option<&'a Multimap>,
     pub extra_query_params: Option<&'a Multimap>,
@@ -1054,6 +1324,33 @@ impl<'a> ListenBucketNotificationArgs<'a> {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct ListenBucketNotificationStreamArgs<'a> {
+    pub extra_headers: Option<&'a Multimap>,
+    pub extra_query_params: Option<&'a Multimap>,
+    pub region: Option<&'a str>,
+    pub bucket: &'a str,
+    pub prefix: Option<&'a str>,
+    pub suffix: Option<&'a str>,
+    pub events: Option<Vec<&'a str>>,
+}
+
+impl<'a> ListenBucketNotificationStreamArgs<'a> {
+    pub fn new(bucket_name: &'a str) -> Result<ListenBucketNotificationStreamArgs<'a>, Error> {
+        check_bucket_name(bucket_name, true)?;
+
+        Ok(ListenBucketNotificationStreamArgs {
+            extra_headers: None,
+            extra_query_params: None,
+            region: None,
+            bucket: bucket_name,
+            prefix: None,
+            suffix: None,
+            events: None,
+        })
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct UploadPartCopyArgs<'a> {
     pub extra_headers: Option<&'a Multimap>,
@@ -1269,7 +1566,7 @@ impl<'a> ComposeSource<'a> {
         let mut copy_source = String::from("/");
         copy_source.push_str(self.bucket);
         copy_source.push('/');
-        copy_source.push_str(self.object);
+        copy_source.push_str(&urlencode_path(self.object));
         if let Some(v) = self.version_id {
             copy_source.push_str("?versionId=");
             copy_source.push_str(&urlencode(v));
@@ -1459,11 +1756,14 @@ pub struct SetBucketPolicyArgs<'a> {
     pub extra_query_params: Option<&'a Multimap>,
     pub region: Option<&'a str>,
     pub bucket: &'a str,
-    pub config: &'a str,
+    pub config: &'a BucketPolicy,
 }
 
 impl<'a> SetBucketPolicyArgs<'a> {
-    pub fn new(bucket_name: &'a str, config: &'a str) -> Result<SetBucketPolicyArgs<'a>, Error> {
+    pub fn new(
+        bucket_name: &'a str,
+        config: &'a BucketPolicy,
+    ) -> Result<SetBucketPolicyArgs<'a>, Error> {
         check_bucket_name(bucket_name, true)?;
 
         Ok(SetBucketPolicyArgs {
@@ -1476,6 +1776,56 @@ impl<'a> SetBucketPolicyArgs<'a> {
     }
 }
 
+pub struct GetAnonymousAccessArgs<'a> {
+    pub extra_headers: Option<&'a Multimap>,
+    pub extra_query_params: Option<&'a Multimap>,
+    pub region: Option<&'a str>,
+    pub bucket: &'a str,
+    pub prefix: &'a str,
+}
+
+impl<'a> GetAnonymousAccessArgs<'a> {
+    pub fn new(bucket_name: &'a str, prefix: &'a str) -> Result<GetAnonymousAccessArgs<'a>, Error> {
+        check_bucket_name(bucket_name, true)?;
+
+        Ok(GetAnonymousAccessArgs {
+            extra_headers: None,
+            extra_query_params: None,
+            region: None,
+            bucket: bucket_name,
+            prefix,
+        })
+    }
+}
+
+pub struct SetAnonymousAccessArgs<'a> {
+    pub extra_headers: Option<&'a Multimap>,
+    pub extra_query_params: Option<&'a Multimap>,
+    pub region: Option<&'a str>,
+    pub bucket: &'a str,
+    pub prefix: &'a str,
+    pub access: AccessLevel,
+}
+
+impl<'a> SetAnonymousAccessArgs<'a> {
+    pub fn new(
+        bucket_name: &'a str,
+        prefix: &'a str,
+        access: AccessLevel,
+    ) -> Result<SetAnonymousAccessArgs<'a>, Error> {
+        check_bucket_name(bucket_name, true)?;
+
+        Ok(SetAnonymousAccessArgs {
+            extra_headers: None,
+            extra_query_params: None,
+            region: None,
+            bucket: bucket_name,
+            prefix,
+            access,
+        })
+    }
+}
+
 pub type DeleteBucketReplicationArgs<'a> = BucketArgs<'a>;
 
 pub type GetBucketReplicationArgs<'a> = BucketArgs<'a>;
@@ -1703,9 +2053,9 @@ pub struct PostPolicy<'a> {
 }
 
 impl<'a> PostPolicy<'a> {
-    const EQ: &str = "eq";
-    const STARTS_WITH: &str = "starts-with";
-    const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+    const EQ: &'static str = "eq";
+    const STARTS_WITH: &'static str = "starts-with";
+    const ALGORITHM: &'static str = "AWS4-HMAC-SHA256";
 
     pub fn new(bucket_name: &'a str, expiration: &'a UtcTime) -> Result<PostPolicy<'a>, Error> {
         check_bucket_name(bucket_name, true)?;
@@ -1913,6 +2263,8 @@ pub struct DownloadObjectArgs<'a> {
     pub ssec: Option<&'a SseCustomerKey>,
     pub filename: &'a str,
     pub overwrite: bool,
+    pub parallel_downloads: usize, // number of ranges fetched concurrently
+    pub part_size: usize,          // size of each range fetched concurrently
 }
 
 impl<'a> DownloadObjectArgs<'a> {
@@ -1939,6 +2291,8 @@ impl<'a> DownloadObjectArgs<'a> {
             ssec: None,
             filename,
             overwrite: false,
+            parallel_downloads: 1,
+            part_size: DEFAULT_DOWNLOAD_PART_SIZE,
         })
     }
 }
@@ -1959,6 +2313,10 @@ pub struct UploadObjectArgs<'a> {
     pub part_size: usize,
     pub part_count: i16,
     pub content_type: &'a str,
+    pub parallel_uploads: usize, // parts uploaded concurrently, each buffered in memory
+    pub max_buffer_size: Option<usize>, // caps memory of in-flight parts; at least one part
+    pub upload_id: Option<&'a str>, // resumes this multipart upload
+    pub checksum: Option<ChecksumAlgorithm>, // sent with each part if multipart
     pub filename: &'a str,
 }
 
@@ -2003,6 +2361,10 @@ impl<'a> UploadObjectArgs<'a> {
             part_size: psize,
             part_count,
             content_type: "application/octet-stream",
+            parallel_uploads: DEFAULT_PARALLEL_UPLOADS,
+            max_buffer_size: None,
+            upload_id: None,
+            checksum: None,
             filename,
         })
     }
@@ -0,0 +1,205 @@
+// MinIO Rust Library for Amazon S3 Compatible Cloud Storage
+// Copyright 2022 MinIO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Additional checksums of object data i.e. `x-amz-checksum-*` headers
+
+use crate::s3::error::Error;
+use crate::s3::transport::{Body, HttpResponse};
+use crate::s3::types::Part;
+use crate::s3::utils::b64encode;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::engine::Engine as _;
+use crc::{Crc, CRC_32_ISCSI, CRC_32_ISO_HDLC};
+use futures_util::stream::try_unfold;
+use hyper::http::HeaderMap;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+static CRC32: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+static CRC32C: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+
+/// Checksum algorithm supported by S3
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChecksumAlgorithm {
+    Crc32,
+    Crc32c,
+    Sha1,
+    Sha256,
+}
+
+impl ChecksumAlgorithm {
+    pub const ALL: [ChecksumAlgorithm; 4] = [
+        ChecksumAlgorithm::Crc32,
+        ChecksumAlgorithm::Crc32c,
+        ChecksumAlgorithm::Sha1,
+        ChecksumAlgorithm::Sha256,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Crc32 => "CRC32",
+            ChecksumAlgorithm::Crc32c => "CRC32C",
+            ChecksumAlgorithm::Sha1 => "SHA1",
+            ChecksumAlgorithm::Sha256 => "SHA256",
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<ChecksumAlgorithm, Error> {
+        ChecksumAlgorithm::ALL
+            .into_iter()
+            .find(|v| v.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| Error::InvalidChecksumAlgorithm(s.to_string()))
+    }
+
+    /// Returns name of header carrying checksum of this algorithm.
+    pub fn header_name(&self) -> String {
+        format!("x-amz-checksum-{}", self.as_str().to_lowercase())
+    }
+
+    /// Returns name of XML element carrying checksum of this algorithm.
+    pub fn xml_tag(&self) -> String {
+        format!("Checksum{}", self.as_str())
+    }
+
+    pub fn hasher(&self) -> Hasher {
+        match self {
+            ChecksumAlgorithm::Crc32 => Hasher::Crc32(CRC32.digest()),
+            ChecksumAlgorithm::Crc32c => Hasher::Crc32c(CRC32C.digest()),
+            ChecksumAlgorithm::Sha1 => Hasher::Sha1(Sha1::new()),
+            ChecksumAlgorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+        }
+    }
+
+    /// Returns base64 encoded checksum of given data.
+    pub fn checksum(&self, data: &[u8]) -> String {
+        let mut hasher = self.hasher();
+        hasher.update(data);
+        hasher.finalize()
+    }
+
+    /// Returns composite checksum of parts i.e. checksum of concatenated part checksums
+    /// followed by part count. Returns None if any part has no checksum.
+    pub fn composite_checksum(&self, parts: &[Part]) -> Option<String> {
+        let mut hasher = self.hasher();
+        for part in parts {
+            hasher.update(&BASE64.decode(part.checksum.as_ref()?).ok()?);
+        }
+        Some(format!("{}-{}", hasher.finalize(), parts.len()))
+    }
+}
+
+impl fmt::Display for ChecksumAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Incremental checksum computation
+#[derive(Clone)]
+pub enum Hasher {
+    Crc32(crc::Digest<'static, u32>),
+    Crc32c(crc::Digest<'static, u32>),
+    Sha1(Sha1),
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Crc32(d) | Hasher::Crc32c(d) => d.update(data),
+            Hasher::Sha1(d) => d.update(data),
+            Hasher::Sha256(d) => d.update(data),
+        }
+    }
+
+    /// Returns base64 encoded checksum.
+    pub fn finalize(self) -> String {
+        match self {
+            Hasher::Crc32(d) | Hasher::Crc32c(d) => b64encode(d.finalize().to_be_bytes()),
+            Hasher::Sha1(d) => b64encode(d.finalize()),
+            Hasher::Sha256(d) => b64encode(d.finalize()),
+        }
+    }
+}
+
+impl fmt::Debug for Hasher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Hasher::Crc32(_) => "Crc32",
+            Hasher::Crc32c(_) => "Crc32c",
+            Hasher::Sha1(_) => "Sha1",
+            Hasher::Sha256(_) => "Sha256",
+        };
+        f.debug_tuple(name).finish_non_exhaustive()
+    }
+}
+
+/// Checksum of an object or part
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checksum {
+    pub algorithm: ChecksumAlgorithm,
+    pub value: String,
+}
+
+impl Checksum {
+    /// Returns first checksum found in given response headers.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Checksum> {
+        ChecksumAlgorithm::ALL.into_iter().find_map(|algorithm| {
+            let value = headers.get(algorithm.header_name())?.to_str().ok()?;
+            Some(Checksum {
+                algorithm,
+                value: value.to_string(),
+            })
+        })
+    }
+
+    /// Returns whether this is a composite checksum of a multipart object, which cannot be
+    /// verified against object data.
+    pub fn is_composite(&self) -> bool {
+        self.value.contains('-')
+    }
+}
+
+/// Wraps body of given response to fail with [Error::ChecksumMismatch] at the end of data if
+/// it does not match given checksum.
+pub(crate) fn verify_checksum(resp: HttpResponse, checksum: Checksum) -> HttpResponse {
+    let status = resp.status();
+    let headers = resp.headers().clone();
+    let hasher = checksum.algorithm.hasher();
+    let stream = try_unfold(
+        (resp.into_body(), hasher, checksum),
+        |(mut body, mut hasher, checksum)| async move {
+            match body.chunk().await? {
+                Some(v) => {
+                    hasher.update(&v);
+                    Ok(Some((v, (body, hasher, checksum))))
+                }
+                None => {
+                    let computed = hasher.finalize();
+                    if computed != checksum.value {
+                        return Err(Error::ChecksumMismatch(
+                            checksum.algorithm.to_string(),
+                            checksum.value,
+                            computed,
+                        ));
+                    }
+                    Ok(None)
+                }
+            }
+        },
+    );
+    HttpResponse::new(status, headers, Body::Stream(Box::pin(stream)))
+}
@@ -14,16 +14,22 @@
 // limitations under the License.
 
 use crate::s3::args::*;
-use crate::s3::creds::Provider;
-use crate::s3::error::{Error, ErrorResponse};
+use crate::s3::checksum::{verify_checksum, Checksum};
+use crate::s3::creds::{credentials_error, Provider};
+use crate::s3::error::{Error, ErrorResponse, S3ErrorCode};
 use crate::s3::http::{BaseUrl, Url};
+use crate::s3::policy::BucketPolicy;
 use crate::s3::response::*;
-use crate::s3::signer::{presign_v4, sign_v4_s3};
+use crate::s3::signer::{
+    chunked_content_length, presign_v4, sign_v4_s3, ChunkSigner, STREAMING_PAYLOAD,
+    UNSIGNED_PAYLOAD,
+};
 use crate::s3::sse::SseCustomerKey;
+use crate::s3::transport::{HttpRequest, HttpResponse, ReqwestTransport, Transport};
 use crate::s3::types::{
-    Bucket, DeleteObject, Directive, Item, LifecycleConfig, NotificationConfig,
-    NotificationRecords, ObjectLockConfig, Part, Quota, ReplicationConfig, RetentionMode,
-    SseConfig,
+    Bucket, DeleteObject, Directive, Item, LifecycleConfig, MultipartUpload, NotificationConfig,
+    NotificationRecord, NotificationRecords, ObjectLockConfig, Part, PartInfo, PayloadSigning,
+    Quota, ReplicationConfig, RetentionMode, RetryPolicy, SseConfig,
 };
 use crate::s3::utils::{
     from_iso8601utc, get_default_text, get_option_text, get_text, md5sum_hash, merge, sha256_hash,
@@ -32,12 +38,20 @@ use crate::s3::utils::{
 use async_recursion::async_recursion;
 use bytes::{Buf, Bytes};
 use dashmap::DashMap;
-use hyper::http::Method;
+use derivative::Derivative;
+use futures_core::Stream;
+use futures_util::StreamExt;
+use hyper::http::{Method, StatusCode};
 use reqwest::header::HeaderMap;
 use std::collections::{HashMap, VecDeque};
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::Read;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::task::{JoinError, JoinSet};
+use tracing::Instrument;
 use xmltree::Element;
 
 fn url_decode(
@@ -111,6 +125,13 @@ fn parse_common_list_objects_response(
     ))
 }
 
+fn parse_option_u16(root: &Element, tag: &str) -> Result<Option<u16>, Error> {
+    match get_option_text(root, tag) {
+        Some(v) if !v.is_empty() => Ok(Some(v.parse::<u16>()?)),
+        _ => Ok(None),
+    }
+}
+
 fn parse_list_objects_contents(
     contents: &mut Vec<Item>,
     root: &mut xmltree::Element,
@@ -208,33 +229,501 @@ fn parse_list_objects_common_prefixes(
     Ok(())
 }
 
+fn listen_bucket_notification_query_params(
+    extra_query_params: Option<&Multimap>,
+    prefix: Option<&str>,
+    suffix: Option<&str>,
+    events: &Option<Vec<&str>>,
+) -> Multimap {
+    let mut query_params = Multimap::new();
+    if let Some(v) = extra_query_params {
+        merge(&mut query_params, v);
+    }
+    if let Some(v) = prefix {
+        query_params.insert(String::from("prefix"), v.to_string());
+    }
+    if let Some(v) = suffix {
+        query_params.insert(String::from("suffix"), v.to_string());
+    }
+    if let Some(v) = events {
+        for e in v.iter() {
+            query_params.insert(String::from("events"), e.to_string());
+        }
+    } else {
+        query_params.insert(String::from("events"), String::from("s3:ObjectCreated:*"));
+        query_params.insert(String::from("events"), String::from("s3:ObjectRemoved:*"));
+        query_params.insert(String::from("events"), String::from("s3:ObjectAccessed:*"));
+    }
+    query_params
+}
+
+// Owned state of listen_bucket_notification_stream, reconnecting whenever the
+// server closes the connection.
+struct NotificationStreamState {
+    client: Client,
+    bucket: String,
+    region: Option<String>,
+    headers: Multimap,
+    query_params: Multimap,
+    resp: Option<HttpResponse>,
+    buf: Vec<u8>,
+    records: VecDeque<NotificationRecord>,
+    attempt: u32,
+    done: bool,
+}
+
+impl NotificationStreamState {
+    async fn connect(&mut self) -> Result<HttpResponse, Error> {
+        if self.client.base_url.aws_host {
+            return Err(Error::UnsupportedApi(String::from(
+                "ListenBucketNotification",
+            )));
+        }
+
+        let region = self
+            .client
+            .get_region(&self.bucket, self.region.as_deref())
+            .await?;
+        self.region = Some(region.clone());
+
+        let mut headers = self.headers.clone();
+        self.client
+            .execute(
+                Method::GET,
+                &region,
+                &mut headers,
+                &self.query_params,
+                Some(&self.bucket),
+                None,
+                None,
+            )
+            .await
+    }
+
+    async fn next_record(&mut self) -> Option<Result<NotificationRecord, Error>> {
+        loop {
+            if let Some(v) = self.records.pop_front() {
+                return Some(Ok(v));
+            }
+
+            if self.done {
+                return None;
+            }
+
+            if let Some(i) = self.buf.iter().position(|&v| v == b'\n') {
+                let data: Vec<u8> = self.buf.drain(..=i).collect();
+                let line = match String::from_utf8(data) {
+                    Ok(v) => v,
+                    Err(e) => return Some(Err(e.into())),
+                };
+                // MinIO sends blank lines to keep the connection alive.
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<NotificationRecords>(line.trim()) {
+                    Ok(v) => self.records.extend(v.records),
+                    Err(e) => return Some(Err(e.into())),
+                }
+                continue;
+            }
+
+            let resp = match self.resp.as_mut() {
+                Some(v) => v,
+                None => {
+                    let result = match self.connect().await {
+                        Ok(v) => {
+                            self.resp = Some(v);
+                            Ok(())
+                        }
+                        Err(e) => {
+                            let retryable = self.client.retry_policy.is_retryable(&e);
+                            self.backoff(e, retryable).await
+                        }
+                    };
+                    if let Err(e) = result {
+                        self.done = true;
+                        return Some(Err(e));
+                    }
+                    continue;
+                }
+            };
+
+            match resp.chunk().await {
+                Ok(Some(v)) => self.buf.extend_from_slice(&v),
+                // Server ended the response; listening goes on with a new one.
+                Ok(None) => {
+                    self.resp = None;
+                    self.buf.clear();
+                }
+                // Broken connection is reestablished like a failed connect.
+                Err(e) => {
+                    self.resp = None;
+                    self.buf.clear();
+                    if let Err(e) = self.backoff(e, true).await {
+                        self.done = true;
+                        return Some(Err(e));
+                    }
+                }
+            }
+        }
+    }
+
+    // Waits before reconnecting after given error, or returns the error if it is not
+    // retryable or failures of this stream reached max attempts of retry policy.
+    async fn backoff(&mut self, e: Error, retryable: bool) -> Result<(), Error> {
+        self.attempt += 1;
+        if !retryable || self.attempt >= self.client.retry_policy.max_attempts {
+            return Err(e);
+        }
+
+        tokio::time::sleep(self.client.retry_policy.delay(self.attempt)).await;
+        Ok(())
+    }
+}
+
+// Arguments for the next page of list_objects.
+enum ListObjectsState<'a> {
+    V1(ListObjectsV1Args<'a>),
+    V2(ListObjectsV2Args<'a>),
+    Versions(ListObjectVersionsArgs<'a>),
+}
+
+// Sub-resources of bucket APIs and names of their S3 APIs without method prefix.
+const BUCKET_SUBRESOURCES: [(&str, &str); 9] = [
+    ("location", "BucketLocation"),
+    ("policy", "BucketPolicy"),
+    ("tagging", "BucketTagging"),
+    ("versioning", "BucketVersioning"),
+    ("lifecycle", "BucketLifecycleConfiguration"),
+    ("notification", "BucketNotificationConfiguration"),
+    ("encryption", "BucketEncryption"),
+    ("replication", "BucketReplication"),
+    ("object-lock", "ObjectLockConfiguration"),
+];
+
+// Returns S3 API name of a request e.g. PutBucketPolicy, used in tracing.
+fn api_name(
+    method: &Method,
+    query_params: &Multimap,
+    headers: &Multimap,
+    bucket_name: Option<&str>,
+    object_name: Option<&str>,
+) -> String {
+    let verb = match *method {
+        Method::GET => "Get",
+        Method::HEAD => "Head",
+        Method::PUT => "Put",
+        Method::POST => "Post",
+        Method::DELETE => "Delete",
+        _ => method.as_str(),
+    };
+    let name = |resource: &str| format!("{}{}", verb, resource);
+    let has = |key: &str| query_params.contains_key(key);
+    let copy = headers.contains_key("x-amz-copy-source");
+
+    let api = match (bucket_name, object_name) {
+        (None, _) => "ListBuckets",
+        (Some(_), Some(_)) => match *method {
+            _ if has("tagging") => return name("ObjectTagging"),
+            _ if has("retention") => return name("ObjectRetention"),
+            _ if has("legal-hold") => return name("ObjectLegalHold"),
+            Method::POST if has("select") => "SelectObjectContent",
+            Method::POST if has("uploads") => "CreateMultipartUpload",
+            Method::POST if has("uploadId") => "CompleteMultipartUpload",
+            Method::PUT if has("uploadId") && copy => "UploadPartCopy",
+            Method::PUT if has("uploadId") => "UploadPart",
+            Method::GET if has("uploadId") => "ListParts",
+            Method::DELETE if has("uploadId") => "AbortMultipartUpload",
+            Method::PUT if copy => "CopyObject",
+            _ => return name("Object"),
+        },
+        (Some(_), None) => match *method {
+            Method::POST if has("delete") => "DeleteObjects",
+            Method::GET if has("events") => "ListenBucketNotification",
+            Method::GET if has("uploads") => "ListMultipartUploads",
+            Method::GET if has("versions") => "ListObjectVersions",
+            Method::GET if has("list-type") => "ListObjectsV2",
+            _ => match BUCKET_SUBRESOURCES.iter().find(|(k, _)| has(k)) {
+                Some((_, v)) => return name(v),
+                None if *method == Method::GET => "ListObjects",
+                None if *method == Method::PUT => "CreateBucket",
+                None => return name("Bucket"),
+            },
+        },
+    };
+    api.to_string()
+}
+
+// Records outcome of a request on its tracing span.
+fn record_result(
+    span: &tracing::Span,
+    result: &Result<HttpResponse, Error>,
+    retries: u32,
+    start: Instant,
+) {
+    span.record("retries", retries);
+    span.record("duration_ms", start.elapsed().as_millis() as u64);
+    let (status, request_id) = match result {
+        Ok(r) => (
+            Some(r.status().as_u16()),
+            r.headers()
+                .get("x-amz-request-id")
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_string()),
+        ),
+        Err(Error::S3Error(er)) => (Some(er.status_code), Some(er.request_id.clone())),
+        Err(e) => (e.status_code(), None),
+    };
+    if let Some(v) = status {
+        span.record("status", v);
+    }
+    if let Some(v) = request_id.filter(|v| !v.is_empty()) {
+        span.record("request_id", v.as_str());
+    }
+}
+
 #[derive(Clone, Debug, Default)]
-pub struct Client<'a> {
+pub struct ClientBuilder {
     base_url: BaseUrl,
-    provider: Option<&'a (dyn Provider + Send + Sync)>,
-    pub ssl_cert_file: String,
-    pub ignore_cert_check: bool,
-    pub user_agent: String,
-    region_map: DashMap<String, String>,
-
-    #[allow(dead_code)]
+    provider: Option<Arc<dyn Provider + Send + Sync>>,
+    ssl_cert_files: Vec<PathBuf>,
+    client_cert: Option<(PathBuf, PathBuf)>,
+    ignore_cert_check: bool,
+    connect_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    proxy: Option<String>,
+    app_info: Option<(String, String)>,
+    retry_policy: RetryPolicy,
+    payload_signing: PayloadSigning,
+    transport: Option<Arc<dyn Transport>>,
     debug: bool,
 }
 
-impl<'a> Client<'a> {
-    pub fn new(base_url: BaseUrl, provider: Option<&(dyn Provider + Send + Sync)>) -> Client {
-        Client {
+impl ClientBuilder {
+    pub fn new(base_url: BaseUrl) -> ClientBuilder {
+        ClientBuilder {
             base_url,
-            provider,
-            ssl_cert_file: String::new(),
-            ignore_cert_check: false,
-            user_agent: String::new(),
-            region_map: DashMap::new(),
-            debug: false,
+            ..Default::default()
+        }
+    }
+
+    pub fn provider(mut self, provider: Option<Arc<dyn Provider + Send + Sync>>) -> Self {
+        self.provider = provider;
+        self
+    }
+
+    /// Trusts CA certificate(s) from given PEM file in addition to system roots. A file may
+    /// contain more than one certificate.
+    pub fn ssl_cert_file(mut self, ssl_cert_file: Option<&Path>) -> Self {
+        self.ssl_cert_files.clear();
+        if let Some(v) = ssl_cert_file {
+            self.ssl_cert_files.push(v.to_path_buf());
         }
+        self
+    }
+
+    pub fn add_ssl_cert_file(mut self, ssl_cert_file: &Path) -> Self {
+        self.ssl_cert_files.push(ssl_cert_file.to_path_buf());
+        self
+    }
+
+    /// Presents PEM encoded certificate chain and PKCS#8 private key to the server (mTLS).
+    pub fn client_cert(mut self, cert_file: &Path, key_file: &Path) -> Self {
+        self.client_cert = Some((cert_file.to_path_buf(), key_file.to_path_buf()));
+        self
+    }
+
+    pub fn ignore_cert_check(mut self, ignore_cert_check: bool) -> Self {
+        self.ignore_cert_check = ignore_cert_check;
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Timeout of whole request i.e. from connect till response body is read.
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
     }
 
-    fn build_headers(
+    /// Routes HTTP and HTTPS traffic through given proxy URL.
+    pub fn proxy(mut self, proxy: Option<&str>) -> Self {
+        self.proxy = proxy.map(String::from);
+        self
+    }
+
+    /// Appends application name and version to User-Agent header.
+    pub fn app_info(mut self, app_name: &str, app_version: &str) -> Self {
+        self.app_info = Some((app_name.to_string(), app_version.to_string()));
+        self
+    }
+
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Sets how payload of object uploads is signed. Defaults to [PayloadSigning::Full].
+    pub fn payload_signing(mut self, payload_signing: PayloadSigning) -> Self {
+        self.payload_signing = payload_signing;
+        self
+    }
+
+    /// Logs headers of every HTTP request and response with credentials redacted, like
+    /// `mc --debug`. Trace is emitted as `tracing` events at DEBUG level.
+    pub fn debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        self.retry_policy.validate()?;
+
+        if let Some(v) = self.connect_timeout {
+            if v.is_zero() {
+                return Err(Error::InvalidClientConfig(String::from(
+                    "connect timeout must be greater than zero",
+                )));
+            }
+        }
+
+        if let Some(v) = self.timeout {
+            if v.is_zero() {
+                return Err(Error::InvalidClientConfig(String::from(
+                    "timeout must be greater than zero",
+                )));
+            }
+        }
+
+        if let Some(v) = &self.proxy {
+            if v.is_empty() {
+                return Err(Error::InvalidClientConfig(String::from(
+                    "proxy URL cannot be empty",
+                )));
+            }
+        }
+
+        if let Some((name, version)) = &self.app_info {
+            if name.trim().is_empty() || version.trim().is_empty() {
+                return Err(Error::InvalidClientConfig(String::from(
+                    "application name/version cannot be empty",
+                )));
+            }
+        }
+
+        if self.client_cert.is_some() && !self.base_url.https {
+            return Err(Error::InvalidClientConfig(String::from(
+                "client certificate requires HTTPS base URL",
+            )));
+        }
+
+        if self.payload_signing == PayloadSigning::Unsigned && !self.base_url.https {
+            return Err(Error::InvalidClientConfig(String::from(
+                "unsigned payload requires HTTPS base URL",
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Sends requests through given transport instead of the default reqwest based one. TLS,
+    /// timeout and proxy settings only apply to the default transport.
+    pub fn transport(mut self, transport: Arc<dyn Transport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    pub fn build(self) -> Result<Client, Error> {
+        self.validate()?;
+
+        let transport = match self.transport {
+            Some(v) => v,
+            None => Arc::new(ReqwestTransport::new(self.build_http_client()?)),
+        };
+
+        let mut user_agent = format!(
+            "MinIO ({}; {}) minio-rs/{}",
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            env!("CARGO_PKG_VERSION")
+        );
+        if let Some((name, version)) = &self.app_info {
+            user_agent.push_str(&format!(" {}/{}", name, version));
+        }
+
+        Ok(Client {
+            transport,
+            base_url: self.base_url,
+            provider: self.provider,
+            user_agent,
+            retry_policy: self.retry_policy,
+            payload_signing: self.payload_signing,
+            region_map: Arc::new(DashMap::new()),
+            debug: self.debug,
+        })
+    }
+
+    fn build_http_client(&self) -> Result<reqwest::Client, Error> {
+        let mut builder = reqwest::Client::builder().no_gzip();
+        if self.ignore_cert_check {
+            builder = builder.danger_accept_invalid_certs(self.ignore_cert_check);
+        }
+        for v in self.ssl_cert_files.iter() {
+            for cert in reqwest::Certificate::from_pem_bundle(&std::fs::read(v)?)? {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+        if let Some((cert_file, key_file)) = &self.client_cert {
+            let identity = reqwest::Identity::from_pkcs8_pem(
+                &std::fs::read(cert_file)?,
+                &std::fs::read(key_file)?,
+            )?;
+            builder = builder.identity(identity);
+        }
+        if let Some(v) = self.connect_timeout {
+            builder = builder.connect_timeout(v);
+        }
+        if let Some(v) = self.timeout {
+            builder = builder.timeout(v);
+        }
+        if let Some(v) = &self.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(v)?);
+        }
+
+        Ok(builder.build()?)
+    }
+}
+
+#[derive(Derivative)]
+#[derivative(Clone, Debug, Default)]
+pub struct Client {
+    #[derivative(Default(value = "Arc::new(ReqwestTransport::default())"))]
+    pub(crate) transport: Arc<dyn Transport>,
+    pub(crate) base_url: BaseUrl,
+    pub(crate) provider: Option<Arc<dyn Provider + Send + Sync>>,
+    user_agent: String,
+    retry_policy: RetryPolicy,
+    payload_signing: PayloadSigning,
+    region_map: Arc<DashMap<String, String>>,
+    debug: bool,
+}
+
+impl Client {
+    pub fn new(
+        base_url: BaseUrl,
+        provider: Option<Arc<dyn Provider + Send + Sync>>,
+    ) -> Result<Client, Error> {
+        ClientBuilder::new(base_url).provider(provider).build()
+    }
+
+    pub fn user_agent(&self) -> &str {
+        &self.user_agent
+    }
+
+    async fn build_headers(
         &self,
         headers: &mut Multimap,
         query_params: &Multimap,
@@ -242,12 +731,9 @@ impl<'a> Client<'a> {
         url: &Url,
         method: &Method,
         data: &[u8],
-    ) {
+    ) -> Result<Option<ChunkSigner>, Error> {
         headers.insert(String::from("Host"), url.host_header_value());
-        headers.insert(
-            String::from("User-Agent"),
-            String::from("MinIO (Linux; x86_64) minio-rs/0.1.0"),
-        );
+        headers.insert(String::from("User-Agent"), self.user_agent.clone());
 
         let mut md5sum = String::new();
         let mut sha256 = String::new();
@@ -261,7 +747,11 @@ impl<'a> Client<'a> {
                     );
                 }
                 if self.provider.is_some() {
-                    sha256 = sha256_hash(data);
+                    // Streaming or unsigned payload is requested by caller.
+                    sha256 = match headers.get("x-amz-content-sha256") {
+                        Some(v) if v == STREAMING_PAYLOAD || v == UNSIGNED_PAYLOAD => v.clone(),
+                        _ => sha256_hash(data),
+                    };
                 } else if !headers.contains_key("Content-MD5") {
                     md5sum = md5sum_hash(data);
                 }
@@ -280,18 +770,34 @@ impl<'a> Client<'a> {
         if !sha256.is_empty() {
             headers.insert(String::from("x-amz-content-sha256"), sha256.clone());
         }
+        if sha256 == STREAMING_PAYLOAD {
+            let encoding = match headers.remove("Content-Encoding") {
+                Some(v) => format!("aws-chunked,{}", v.join(",")),
+                None => String::from("aws-chunked"),
+            };
+            headers.insert(String::from("Content-Encoding"), encoding);
+            headers.insert(
+                String::from("x-amz-decoded-content-length"),
+                data.len().to_string(),
+            );
+            headers.remove("Content-Length");
+            headers.insert(
+                String::from("Content-Length"),
+                chunked_content_length(data.len()).to_string(),
+            );
+        }
         let date = utc_now();
         headers.insert(String::from("x-amz-date"), to_amz_date(date));
 
-        if let Some(p) = self.provider {
-            let creds = p.fetch();
+        if let Some(p) = &self.provider {
+            let creds = p.fetch().await.map_err(credentials_error)?;
             if creds.session_token.is_some() {
                 headers.insert(
                     String::from("X-Amz-Security-Token"),
                     creds.session_token.unwrap(),
                 );
             }
-            sign_v4_s3(
+            let signature = sign_v4_s3(
                 method,
                 &url.path,
                 region,
@@ -302,25 +808,31 @@ impl<'a> Client<'a> {
                 &sha256,
                 date,
             );
+            if sha256 == STREAMING_PAYLOAD {
+                return Ok(Some(ChunkSigner::new(
+                    &creds.secret_key,
+                    date,
+                    region,
+                    signature,
+                )));
+            }
         }
+
+        Ok(None)
     }
 
     fn handle_redirect_response(
         &self,
         status_code: u16,
-        method: &Method,
-        header_map: &reqwest::header::HeaderMap,
-        bucket_name: Option<&str>,
-        retry: bool,
-    ) -> Result<(String, String), Error> {
-        let (mut code, mut message) = match status_code {
+        header_map: &HeaderMap,
+    ) -> Result<(S3ErrorCode, String), Error> {
+        let (code, mut message) = match status_code {
             301 => (
-                String::from("PermanentRedirect"),
+                S3ErrorCode::PermanentRedirect,
                 String::from("Moved Permanently"),
             ),
-            307 => (String::from("Redirect"), String::from("Temporary redirect")),
-            400 => (String::from("BadRequest"), String::from("Bad request")),
-            _ => (String::new(), String::new()),
+            307 => (S3ErrorCode::Redirect, String::from("Temporary redirect")),
+            _ => (S3ErrorCode::BadRequest, String::from("Bad request")),
         };
 
         let region = match header_map.get("x-amz-bucket-region") {
@@ -328,20 +840,11 @@ impl<'a> Client<'a> {
             _ => "",
         };
 
-        if !message.is_empty() && !region.is_empty() {
+        if !region.is_empty() {
             message.push_str("; use region ");
             message.push_str(region);
         }
 
-        if retry && !region.is_empty() && method == Method::HEAD {
-            if let Some(v) = bucket_name {
-                if self.region_map.contains_key(v) {
-                    code = String::from("RetryHead");
-                    message = String::new();
-                }
-            }
-        }
-
         Ok((code, message))
     }
 
@@ -349,24 +852,22 @@ impl<'a> Client<'a> {
         &self,
         body: &mut Bytes,
         status_code: u16,
-        header_map: &reqwest::header::HeaderMap,
-        method: &Method,
+        header_map: &HeaderMap,
         resource: &str,
         bucket_name: Option<&str>,
         object_name: Option<&str>,
-        retry: bool,
     ) -> Error {
         if !body.is_empty() {
             return match header_map.get("Content-Type") {
                 Some(v) => match v.to_str() {
                     Ok(s) => match s.to_lowercase() {
                         _ if s.contains("application/xml") => match ErrorResponse::parse(body) {
-                            Ok(v) => Error::S3Error(v),
+                            Ok(v) => Error::S3Error(v.with_response(status_code, header_map)),
                             Err(e) => e,
                         },
                         _ if s.contains("application/json") => {
                             match ErrorResponse::parse_json(body) {
-                                Ok(v) => Error::S3Error(v),
+                                Ok(v) => Error::S3Error(v.with_response(status_code, header_map)),
                                 Err(e) => e,
                             }
                         }
@@ -379,49 +880,43 @@ impl<'a> Client<'a> {
         }
 
         let (code, message) = match status_code {
-            301 | 307 | 400 => match self.handle_redirect_response(
-                status_code,
-                method,
-                header_map,
-                bucket_name,
-                retry,
-            ) {
+            301 | 307 | 400 => match self.handle_redirect_response(status_code, header_map) {
                 Ok(v) => v,
                 Err(e) => return e,
             },
-            403 => (String::from("AccessDenied"), String::from("Access denied")),
+            403 => (S3ErrorCode::AccessDenied, String::from("Access denied")),
             404 => match object_name {
                 Some(_) => (
-                    String::from("NoSuchKey"),
+                    S3ErrorCode::NoSuchKey,
                     String::from("Object does not exist"),
                 ),
                 _ => match bucket_name {
                     Some(_) => (
-                        String::from("NoSuchBucket"),
+                        S3ErrorCode::NoSuchBucket,
                         String::from("Bucket does not exist"),
                     ),
                     _ => (
-                        String::from("ResourceNotFound"),
+                        S3ErrorCode::ResourceNotFound,
                         String::from("Request resource not found"),
                     ),
                 },
             },
             405 => (
-                String::from("MethodNotAllowed"),
+                S3ErrorCode::MethodNotAllowed,
                 String::from("The specified method is not allowed against this resource"),
             ),
             409 => match bucket_name {
                 Some(_) => (
-                    String::from("NoSuchBucket"),
+                    S3ErrorCode::NoSuchBucket,
                     String::from("Bucket does not exist"),
                 ),
                 _ => (
-                    String::from("ResourceConflict"),
+                    S3ErrorCode::ResourceConflict,
                     String::from("Request resource conflicts"),
                 ),
             },
             501 => (
-                String::from("MethodNotAllowed"),
+                S3ErrorCode::MethodNotAllowed,
                 String::from("The specified method is not allowed against this resource"),
             ),
             _ => return Error::ServerError(status_code),
@@ -451,9 +946,23 @@ impl<'a> Client<'a> {
             host_id,
             bucket_name: bucket_name.unwrap_or_default().to_string(),
             object_name: object_name.unwrap_or_default().to_string(),
+            status_code,
+            headers: header_map.clone(),
         })
     }
 
+    // Sends request through the transport, logging request and response in HTTP trace mode.
+    pub(crate) async fn send(&self, req: HttpRequest) -> Result<HttpResponse, Error> {
+        if !self.debug {
+            return self.transport.send(req).await;
+        }
+
+        tracing::debug!("HTTP request\n{}", req.trace());
+        let resp = self.transport.send(req).await?;
+        tracing::debug!("HTTP response\n{}", resp.trace());
+        Ok(resp)
+    }
+
     pub async fn do_execute(
         &self,
         method: Method,
@@ -462,41 +971,43 @@ impl<'a> Client<'a> {
         query_params: &Multimap,
         bucket_name: Option<&str>,
         object_name: Option<&str>,
-        data: Option<&[u8]>,
-        retry: bool,
-    ) -> Result<reqwest::Response, Error> {
+        data: Option<Bytes>,
+    ) -> Result<HttpResponse, Error> {
         let body = data.unwrap_or_default();
         let url =
             self.base_url
                 .build_url(&method, region, query_params, bucket_name, object_name)?;
-        self.build_headers(headers, query_params, region, &url, &method, body);
-
-        let mut builder = reqwest::Client::builder().no_gzip();
-        if self.ignore_cert_check {
-            builder = builder.danger_accept_invalid_certs(self.ignore_cert_check);
-        }
-        if !self.ssl_cert_file.is_empty() {
-            let mut buf = Vec::new();
-            File::open(&self.ssl_cert_file)?.read_to_end(&mut buf)?;
-            let cert = reqwest::Certificate::from_pem(&buf)?;
-            builder = builder.add_root_certificate(cert);
-        }
-
-        let client = builder.build()?;
-
-        let mut req = client.request(method.clone(), url.to_string());
-
-        for (key, values) in headers.iter_all() {
-            for value in values {
-                req = req.header(key, value);
+        // Only object and part uploads are sent with streaming or unsigned payload.
+        if self.provider.is_some()
+            && method == Method::PUT
+            && object_name.is_some()
+            && !body.is_empty()
+            && query_params
+                .keys()
+                .all(|k| k == "uploadId" || k == "partNumber")
+        {
+            let sha256 = match self.payload_signing {
+                PayloadSigning::Full => None,
+                PayloadSigning::Unsigned => Some(UNSIGNED_PAYLOAD),
+                PayloadSigning::Streaming => Some(STREAMING_PAYLOAD),
+            };
+            if let Some(v) = sha256 {
+                headers.insert(String::from("x-amz-content-sha256"), v.to_string());
             }
         }
+        let chunk_signer = self
+            .build_headers(headers, query_params, region, &url, &method, &body)
+            .await?;
 
-        if method == Method::PUT || method == Method::POST {
-            req = req.body(body.to_vec());
-        }
+        let req = HttpRequest {
+            method: method.clone(),
+            url: url.clone(),
+            headers: headers.clone(),
+            body,
+            chunk_signer,
+        };
 
-        let resp = req.send().await?;
+        let resp = self.send(req).await?;
         if resp.status().is_success() {
             return Ok(resp);
         }
@@ -504,31 +1015,44 @@ impl<'a> Client<'a> {
         let status_code = resp.status().as_u16();
         let header_map = resp.headers().clone();
         let mut body = resp.bytes().await?;
+        if self.debug {
+            tracing::debug!("HTTP response body\n{}", String::from_utf8_lossy(&body));
+        }
         let e = self.get_error_response(
             &mut body,
             status_code,
             &header_map,
-            &method,
             &url.path,
             bucket_name,
             object_name,
-            retry,
         );
 
-        match e {
-            Error::S3Error(ref er) => {
-                if er.code == "NoSuchBucket" || er.code == "RetryHead" {
-                    if let Some(v) = bucket_name {
-                        self.region_map.remove(v);
-                    }
+        if let Error::S3Error(ref er) = e {
+            if er.code == S3ErrorCode::NoSuchBucket {
+                if let Some(v) = bucket_name {
+                    self.region_map.remove(v);
                 }
             }
-            _ => return Err(e),
-        };
+        }
 
         Err(e)
     }
 
+    // Returns whether HEAD request failed because cached region of the bucket is stale, in
+    // which case the cached region is dropped.
+    fn is_stale_region(&self, method: &Method, e: &Error, bucket_name: Option<&str>) -> bool {
+        let (Error::S3Error(er), Some(bucket)) = (e, bucket_name) else {
+            return false;
+        };
+        if *method != Method::HEAD
+            || !matches!(er.status_code, 301 | 307 | 400)
+            || !er.headers.contains_key("x-amz-bucket-region")
+        {
+            return false;
+        }
+        self.region_map.remove(bucket).is_some()
+    }
+
     pub async fn execute(
         &self,
         method: Method,
@@ -537,43 +1061,190 @@ impl<'a> Client<'a> {
         query_params: &Multimap,
         bucket_name: Option<&str>,
         object_name: Option<&str>,
-        data: Option<&[u8]>,
-    ) -> Result<reqwest::Response, Error> {
-        let res = self
-            .do_execute(
-                method.clone(),
-                region,
-                headers,
-                query_params,
-                bucket_name,
-                object_name,
-                data,
-                true,
-            )
-            .await;
-        match res {
-            Ok(r) => return Ok(r),
-            Err(e) => match e {
-                Error::S3Error(ref er) => {
-                    if er.code != "RetryHead" {
-                        return Err(e);
+        data: Option<Bytes>,
+    ) -> Result<HttpResponse, Error> {
+        let span = tracing::info_span!(
+            "s3_request",
+            api = %api_name(&method, query_params, headers, bucket_name, object_name),
+            bucket = bucket_name.unwrap_or_default(),
+            object = object_name.unwrap_or_default(),
+            region = %region,
+            status = tracing::field::Empty,
+            request_id = tracing::field::Empty,
+            duration_ms = tracing::field::Empty,
+            retries = tracing::field::Empty,
+        );
+        let orig_headers = headers.clone();
+        let last_headers = Mutex::new(Multimap::new());
+        let retry_head = AtomicBool::new(true);
+        let result = self
+            .with_retry(&method, span, || {
+                let (method, orig_headers, last_headers, retry_head) =
+                    (&method, &orig_headers, &last_headers, &retry_head);
+                let data = data.clone();
+                async move {
+                    // Each attempt is signed afresh from caller provided headers.
+                    let mut headers = orig_headers.clone();
+                    let mut result = self
+                        .do_execute(
+                            method.clone(),
+                            region,
+                            &mut headers,
+                            query_params,
+                            bucket_name,
+                            object_name,
+                            data.clone(),
+                        )
+                        .await;
+
+                    // Retry HEAD only once on stale region.
+                    if let Err(e) = &result {
+                        if retry_head.load(Ordering::SeqCst)
+                            && self.is_stale_region(method, e, bucket_name)
+                        {
+                            retry_head.store(false, Ordering::SeqCst);
+                            headers = orig_headers.clone();
+                            result = self
+                                .do_execute(
+                                    method.clone(),
+                                    region,
+                                    &mut headers,
+                                    query_params,
+                                    bucket_name,
+                                    object_name,
+                                    data,
+                                )
+                                .await;
+                        }
                     }
+
+                    *last_headers.lock().unwrap() = headers;
+                    result
                 }
-                _ => return Err(e),
-            },
+            })
+            .await;
+
+        *headers = last_headers.into_inner().unwrap();
+        result
+    }
+
+    // Runs request attempts until one succeeds or fails with an error not to be retried per
+    // retry policy. Outcome is recorded on given span.
+    async fn with_retry<F, Fut>(
+        &self,
+        method: &Method,
+        span: tracing::Span,
+        mut send: F,
+    ) -> Result<HttpResponse, Error>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<HttpResponse, Error>>,
+    {
+        let start = Instant::now();
+        let replayable = self.retry_policy.is_replayable(method);
+        let mut attempt = 0_u32;
+        let mut retries = 0_u32;
+        let result = async {
+            loop {
+                let e = match send().await {
+                    Ok(r) => return Ok(r),
+                    Err(e) => e,
+                };
+
+                attempt += 1;
+                if !replayable
+                    || attempt >= self.retry_policy.max_attempts
+                    || !self.retry_policy.is_retryable(&e)
+                {
+                    return Err(e);
+                }
+
+                retries += 1;
+                tokio::time::sleep(self.retry_policy.delay(attempt)).await;
+            }
+        }
+        .instrument(span.clone())
+        .await;
+
+        record_result(&span, &result, retries, start);
+        result
+    }
+
+    /// Executes MinIO admin API. Requests are always path style and signed as S3 service in
+    /// the configured region, and errors are parsed from JSON response.
+    pub(crate) async fn execute_admin(
+        &self,
+        method: Method,
+        api: &str,
+        extra_headers: Option<&Multimap>,
+        query_params: &Multimap,
+        data: Option<Bytes>,
+    ) -> Result<HttpResponse, Error> {
+        if self.base_url.aws_host {
+            return Err(Error::UnsupportedApi(String::from("Admin")));
+        }
+
+        let region = if self.base_url.region.is_empty() {
+            String::from("us-east-1")
+        } else {
+            self.base_url.region.clone()
         };
+        let url = self
+            .base_url
+            .build_path_url(&format!("/minio/admin/v3/{}", api), query_params);
+        let body = data.unwrap_or_default();
 
-        // Retry only once on RetryHead error.
-        self.do_execute(
-            method.clone(),
-            region,
-            headers,
-            query_params,
-            bucket_name,
-            object_name,
-            data,
-            false,
-        )
+        let span = tracing::info_span!(
+            "s3_request",
+            api = %format!("admin/{}", api),
+            region = %region,
+            status = tracing::field::Empty,
+            request_id = tracing::field::Empty,
+            duration_ms = tracing::field::Empty,
+            retries = tracing::field::Empty,
+        );
+        self.with_retry(&method, span, || {
+            let (method, region, url, body) = (&method, &region, &url, body.clone());
+            async move {
+                let mut headers = Multimap::new();
+                if let Some(v) = extra_headers {
+                    merge(&mut headers, v);
+                }
+                self.build_headers(&mut headers, query_params, region, url, method, &body)
+                    .await?;
+
+                let resp = self
+                    .send(HttpRequest {
+                        method: method.clone(),
+                        url: url.clone(),
+                        headers,
+                        body,
+                        chunk_signer: None,
+                    })
+                    .await?;
+                if resp.status().is_success() {
+                    return Ok(resp);
+                }
+
+                let status_code = resp.status().as_u16();
+                let header_map = resp.headers().clone();
+                let mut body = resp.bytes().await?;
+                if self.debug {
+                    tracing::debug!("HTTP response body\n{}", String::from_utf8_lossy(&body));
+                }
+                Err(if body.is_empty() {
+                    Error::ServerError(status_code)
+                } else {
+                    match ErrorResponse::parse_json(&mut body) {
+                        Ok(v) => Error::S3Error(v.with_response(status_code, &header_map)),
+                        Err(_) => Error::InvalidResponse(
+                            status_code,
+                            String::from_utf8_lossy(&body).to_string(),
+                        ),
+                    }
+                })
+            }
+        })
         .await
     }
 
@@ -676,7 +1347,7 @@ impl<'a> Client<'a> {
             Ok(r) => region = r,
             Err(e) => match e {
                 Error::S3Error(ref er) => {
-                    if er.code == "NoSuchBucket" {
+                    if er.code == S3ErrorCode::NoSuchBucket {
                         return Ok(false);
                     }
                     return Err(e);
@@ -709,7 +1380,7 @@ impl<'a> Client<'a> {
             Ok(_) => Ok(true),
             Err(e) => match e {
                 Error::S3Error(ref er) => {
-                    if er.code == "NoSuchBucket" {
+                    if er.code == S3ErrorCode::NoSuchBucket {
                         return Ok(false);
                     }
                     Err(e)
@@ -727,9 +1398,13 @@ impl<'a> Client<'a> {
 
         let mut data = String::from("<CompleteMultipartUpload>");
         for part in args.parts.iter() {
+            let checksum = match (args.checksum, &part.checksum) {
+                (Some(a), Some(v)) => format!("<{0}>{1}</{0}>", a.xml_tag(), v),
+                _ => String::new(),
+            };
             let s = format!(
-                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
-                part.number, part.etag
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag>{}</Part>",
+                part.number, part.etag, checksum
             );
             data.push_str(&s);
         }
@@ -760,13 +1435,42 @@ impl<'a> Client<'a> {
                 &query_params,
                 Some(args.bucket),
                 Some(args.object),
-                Some(b),
+                Some(Bytes::from(data)),
             )
             .await?;
         let header_map = resp.headers().clone();
         let body = resp.bytes().await?;
         let root = Element::parse(body.reader())?;
 
+        let checksum = match args.checksum {
+            Some(a) => match get_option_text(&root, &a.xml_tag()) {
+                Some(v) => {
+                    if let Some(expected) = a.composite_checksum(args.parts) {
+                        if v != expected {
+                            // Server has already stored the object, hence it is removed on
+                            // best effort basis and mismatch is reported regardless.
+                            let version_id = header_map
+                                .get("x-amz-version-id")
+                                .and_then(|v| v.to_str().ok());
+                            if let Ok(mut roargs) = RemoveObjectArgs::new(args.bucket, args.object)
+                            {
+                                roargs.region = args.region;
+                                roargs.version_id = version_id;
+                                let _ = self.remove_object(&roargs).await;
+                            }
+                            return Err(Error::ChecksumMismatch(a.to_string(), expected, v));
+                        }
+                    }
+                    Some(Checksum {
+                        algorithm: a,
+                        value: v,
+                    })
+                }
+                None => None,
+            },
+            None => None,
+        };
+
         Ok(CompleteMultipartUploadResponse {
             headers: header_map.clone(),
             bucket_name: get_text(&root, "Bucket")?,
@@ -777,12 +1481,13 @@ impl<'a> Client<'a> {
                 Some(v) => Some(v.to_str()?.to_string()),
                 None => None,
             },
+            checksum,
         })
     }
 
     async fn calculate_part_count(
         &self,
-        sources: &'a mut Vec<ComposeSource<'_>>,
+        sources: &mut Vec<ComposeSource<'_>>,
     ) -> Result<u16, Error> {
         let mut object_size = 0_usize;
         let mut i = 0;
@@ -966,6 +1671,7 @@ impl<'a> Client<'a> {
                 parts.push(Part {
                     number: part_number,
                     etag: resp.etag,
+                    checksum: None,
                 });
             } else {
                 while size > 0 {
@@ -996,6 +1702,7 @@ impl<'a> Client<'a> {
                     parts.push(Part {
                         number: part_number,
                         etag: resp.etag,
+                        checksum: None,
                     });
 
                     offset = start_bytes;
@@ -1135,6 +1842,7 @@ impl<'a> Client<'a> {
                 Some(v) => Some(v.to_str()?.to_string()),
                 None => None,
             },
+            checksum: None,
         })
     }
 
@@ -1154,6 +1862,12 @@ impl<'a> Client<'a> {
                 String::from("application/octet-stream"),
             );
         }
+        if let Some(v) = args.checksum {
+            headers.insert(
+                String::from("x-amz-checksum-algorithm"),
+                v.as_str().to_string(),
+            );
+        }
 
         let mut query_params = Multimap::new();
         if let Some(v) = &args.extra_query_params {
@@ -1221,7 +1935,7 @@ impl<'a> Client<'a> {
             }),
             Err(e) => match e {
                 Error::S3Error(ref err) => {
-                    if err.code == "ServerSideEncryptionConfigurationNotFoundError" {
+                    if err.code == S3ErrorCode::ServerSideEncryptionConfigurationNotFoundError {
                         return Ok(DeleteBucketEncryptionResponse {
                             headers: HeaderMap::new(),
                             region: region.clone(),
@@ -1263,7 +1977,9 @@ impl<'a> Client<'a> {
                 &query_params,
                 Some(args.bucket),
                 Some(args.object),
-                Some(b"<LegalHold><Status>OFF</Status></LegalHold>"),
+                Some(Bytes::from_static(
+                    b"<LegalHold><Status>OFF</Status></LegalHold>",
+                )),
             )
             .await?;
 
@@ -1366,7 +2082,7 @@ impl<'a> Client<'a> {
             }),
             Err(e) => match e {
                 Error::S3Error(ref err) => {
-                    if err.code == "NoSuchBucketPolicy" {
+                    if err.code == S3ErrorCode::NoSuchBucketPolicy {
                         return Ok(DeleteBucketPolicyResponse {
                             headers: HeaderMap::new(),
                             region: region.clone(),
@@ -1416,7 +2132,7 @@ impl<'a> Client<'a> {
             }),
             Err(e) => match e {
                 Error::S3Error(ref err) => {
-                    if err.code == "ReplicationConfigurationNotFoundError" {
+                    if err.code == S3ErrorCode::ReplicationConfigurationNotFoundError {
                         return Ok(DeleteBucketReplicationResponse {
                             headers: HeaderMap::new(),
                             region: region.clone(),
@@ -1529,46 +2245,131 @@ impl<'a> Client<'a> {
         &self,
         args: &DownloadObjectArgs<'_>,
     ) -> Result<DownloadObjectResponse, Error> {
-        let mut resp = self
-            .get_object(&GetObjectArgs {
-                extra_headers: args.extra_headers,
-                extra_query_params: args.extra_query_params,
-                region: args.region,
-                bucket: args.bucket,
-                object: args.object,
-                version_id: args.version_id,
-                ssec: args.ssec,
-                offset: None,
-                length: None,
-                match_etag: None,
-                not_match_etag: None,
-                modified_since: None,
-                unmodified_since: None,
-            })
+        if !args.overwrite && Path::new(args.filename).exists() {
+            return Err(Error::IOError(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{} already exists", args.filename),
+            )));
+        }
+
+        let mut soargs = StatObjectArgs::new(args.bucket, args.object)?;
+        soargs.extra_headers = args.extra_headers;
+        soargs.extra_query_params = args.extra_query_params;
+        soargs.region = args.region;
+        soargs.version_id = args.version_id;
+        soargs.ssec = args.ssec;
+        let stat = self.stat_object(&soargs).await?;
+
+        // Partial data is kept per ETag, so a changed object never resumes from stale data.
+        let part_file = format!("{}.{}.part.minio", args.filename, stat.etag);
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&part_file)
             .await?;
+        let mut offset = file.metadata().await?.len() as usize;
+        if offset > stat.size {
+            file.set_len(0).await?;
+            offset = 0;
+        }
 
-        let mut file = match args.overwrite {
-            true => File::create(args.filename)?,
-            false => File::options()
-                .write(true)
-                .truncate(true)
-                .create_new(true)
-                .open(args.filename)?,
+        let get_args = |offset: Option<usize>, length: Option<usize>| GetObjectArgs {
+            extra_headers: args.extra_headers,
+            extra_query_params: args.extra_query_params,
+            region: args.region,
+            bucket: args.bucket,
+            object: args.object,
+            version_id: args.version_id,
+            ssec: args.ssec,
+            offset,
+            length,
+            match_etag: Some(&stat.etag),
+            not_match_etag: None,
+            modified_since: None,
+            unmodified_since: None,
+            checksum_mode: false,
         };
-        while let Some(v) = resp.chunk().await? {
-            file.write_all(&v)?;
+
+        if offset < stat.size && args.parallel_downloads > 1 {
+            let part_size = args.part_size.max(1);
+            let ranges = (offset..stat.size)
+                .step_by(part_size)
+                .map(|o| (o, std::cmp::min(part_size, stat.size - o)));
+
+            // Ranges are fetched concurrently but written in order, so the part
+            // file always holds a contiguous prefix of the object.
+            let mut parts = futures_util::stream::iter(ranges)
+                .map(|(o, l)| {
+                    let args = get_args(Some(o), Some(l));
+                    async move { self.get_object(&args).await?.bytes().await }
+                })
+                .buffered(args.parallel_downloads);
+            while let Some(v) = parts.next().await {
+                file.write_all(&v?).await?;
+            }
+        } else if offset < stat.size {
+            let mut resp = self
+                .get_object(&get_args((offset > 0).then_some(offset), None))
+                .await?;
+            while let Some(v) = resp.chunk().await? {
+                file.write_all(&v).await?;
+            }
+        }
+        file.sync_all().await?;
+        std::mem::drop(file);
+
+        if let Err(e) = Client::verify_download(&part_file, &stat, args.ssec.is_some()).await {
+            tokio::fs::remove_file(&part_file).await?;
+            return Err(e);
         }
-        file.sync_all()?;
+        tokio::fs::rename(&part_file, args.filename).await?;
 
         Ok(DownloadObjectResponse {
-            headers: resp.headers().clone(),
-            region: args.region.map_or(String::new(), String::from),
+            headers: stat.headers,
+            region: stat.region,
             bucket_name: args.bucket.to_string(),
             object_name: args.object.to_string(),
             version_id: args.version_id.as_ref().map(|v| v.to_string()),
         })
     }
 
+    async fn verify_download(
+        filename: &str,
+        stat: &StatObjectResponse,
+        encrypted: bool,
+    ) -> Result<(), Error> {
+        let size = tokio::fs::metadata(filename).await?.len() as usize;
+        if size != stat.size {
+            return Err(Error::InsufficientData(stat.size, size));
+        }
+
+        // ETag is MD5 of the content only for single part uploads without encryption.
+        if encrypted
+            || stat.etag.contains('-')
+            || stat.headers.contains_key("x-amz-server-side-encryption")
+        {
+            return Ok(());
+        }
+
+        let mut file = tokio::fs::File::open(filename).await?;
+        let mut ctx = md5::Context::new();
+        let mut buf = vec![0_u8; 1024 * 1024];
+        loop {
+            let n = file.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            ctx.consume(&buf[..n]);
+        }
+
+        let etag = format!("{:x}", ctx.compute());
+        if etag != stat.etag {
+            return Err(Error::EtagMismatch(stat.etag.clone(), etag));
+        }
+
+        Ok(())
+    }
+
     pub async fn enable_object_legal_hold(
         &self,
         args: &EnableObjectLegalHoldArgs<'_>,
@@ -1597,7 +2398,9 @@ impl<'a> Client<'a> {
                 &query_params,
                 Some(args.bucket),
                 Some(args.object),
-                Some(b"<LegalHold><Status>ON</Status></LegalHold>"),
+                Some(Bytes::from_static(
+                    b"<LegalHold><Status>ON</Status></LegalHold>",
+                )),
             )
             .await?;
 
@@ -1705,7 +2508,7 @@ impl<'a> Client<'a> {
             }
             Err(e) => match e {
                 Error::S3Error(ref err) => {
-                    if err.code == "NoSuchLifecycleConfiguration" {
+                    if err.code == S3ErrorCode::NoSuchLifecycleConfiguration {
                         return Ok(GetBucketLifecycleResponse {
                             headers: HeaderMap::new(),
                             region: region.clone(),
@@ -1791,21 +2594,23 @@ impl<'a> Client<'a> {
             .await
         {
             Ok(resp) => {
+                let header_map = resp.headers().clone();
+                let body = resp.bytes().await?;
                 return Ok(GetBucketPolicyResponse {
-                    headers: resp.headers().clone(),
+                    headers: header_map,
                     region: region.clone(),
                     bucket_name: args.bucket.to_string(),
-                    config: resp.text().await?,
-                })
+                    config: serde_json::from_slice(&body)?,
+                });
             }
             Err(e) => match e {
                 Error::S3Error(ref err) => {
-                    if err.code == "NoSuchBucketPolicy" {
+                    if err.code == S3ErrorCode::NoSuchBucketPolicy {
                         return Ok(GetBucketPolicyResponse {
                             headers: HeaderMap::new(),
                             region: region.clone(),
                             bucket_name: args.bucket.to_string(),
-                            config: String::from("{}"),
+                            config: BucketPolicy::default(),
                         });
                     }
                     Err(e)
@@ -1815,6 +2620,30 @@ impl<'a> Client<'a> {
         }
     }
 
+    /// Returns effective anonymous access to objects under given prefix as granted by bucket
+    /// policy.
+    pub async fn get_anonymous_access(
+        &self,
+        args: &GetAnonymousAccessArgs<'_>,
+    ) -> Result<GetAnonymousAccessResponse, Error> {
+        let resp = self
+            .get_bucket_policy(&GetBucketPolicyArgs {
+                extra_headers: args.extra_headers,
+                extra_query_params: args.extra_query_params,
+                region: args.region,
+                bucket: args.bucket,
+            })
+            .await?;
+
+        Ok(GetAnonymousAccessResponse {
+            headers: resp.headers,
+            region: resp.region,
+            bucket_name: resp.bucket_name,
+            prefix: args.prefix.to_string(),
+            access: resp.config.access(args.bucket, args.prefix),
+        })
+    }
+
     pub async fn get_bucket_replication(
         &self,
         args: &GetBucketReplicationArgs<'_>,
@@ -1910,7 +2739,7 @@ impl<'a> Client<'a> {
             }
             Err(e) => match e {
                 Error::S3Error(ref err) => {
-                    if err.code == "NoSuchTagSet" {
+                    if err.code == S3ErrorCode::NoSuchTagSet {
                         return Ok(GetBucketTagsResponse {
                             headers: HeaderMap::new(),
                             region: region.clone(),
@@ -1967,7 +2796,7 @@ impl<'a> Client<'a> {
         })
     }
 
-    pub async fn get_object(&self, args: &GetObjectArgs<'_>) -> Result<reqwest::Response, Error> {
+    pub async fn get_object(&self, args: &GetObjectArgs<'_>) -> Result<HttpResponse, Error> {
         if args.ssec.is_some() && !self.base_url.https {
             return Err(Error::SseTlsRequired(None));
         }
@@ -1988,16 +2817,28 @@ impl<'a> Client<'a> {
             query_params.insert(String::from("versionId"), v.to_string());
         }
 
-        self.execute(
-            Method::GET,
-            &region,
-            &mut headers,
-            &query_params,
-            Some(args.bucket),
-            Some(args.object),
-            None,
-        )
-        .await
+        let resp = self
+            .execute(
+                Method::GET,
+                &region,
+                &mut headers,
+                &query_params,
+                Some(args.bucket),
+                Some(args.object),
+                None,
+            )
+            .await?;
+
+        // Checksum covers whole object, hence partial and composite checksums are not verified.
+        if args.checksum_mode && resp.status() == StatusCode::OK {
+            if let Some(v) = Checksum::from_headers(resp.headers()) {
+                if !v.is_composite() {
+                    return Ok(verify_checksum(resp, v));
+                }
+            }
+        }
+
+        Ok(resp)
     }
 
     pub async fn get_object_lock_config(
@@ -2096,7 +2937,7 @@ impl<'a> Client<'a> {
             }
             Err(e) => match e {
                 Error::S3Error(ref err) => {
-                    if err.code == "NoSuchObjectLockConfiguration" {
+                    if err.code == S3ErrorCode::NoSuchObjectLockConfiguration {
                         return Ok(GetObjectRetentionResponse {
                             headers: HeaderMap::new(),
                             region: region.clone(),
@@ -2193,8 +3034,8 @@ impl<'a> Client<'a> {
             Some(args.object),
         )?;
 
-        if let Some(p) = self.provider {
-            let creds = p.fetch();
+        if let Some(p) = &self.provider {
+            let creds = p.fetch().await.map_err(credentials_error)?;
             if let Some(t) = creds.session_token {
                 query_params.insert(String::from("X-Amz-Security-Token"), t);
             }
@@ -2206,7 +3047,7 @@ impl<'a> Client<'a> {
 
             presign_v4(
                 &args.method,
-                &url.host,
+                &url.host_header_value(),
                 &url.path,
                 &region,
                 &mut query_params,
@@ -2239,7 +3080,13 @@ impl<'a> Client<'a> {
         }
 
         let region = self.get_region(policy.bucket, policy.region).await?;
-        let creds = self.provider.unwrap().fetch();
+        let creds = self
+            .provider
+            .as_ref()
+            .unwrap()
+            .fetch()
+            .await
+            .map_err(credentials_error)?;
         policy.form_data(
             creds.access_key,
             creds.secret_key,
@@ -2295,7 +3142,7 @@ impl<'a> Client<'a> {
             }
             Err(e) => match e {
                 Error::S3Error(ref err) => {
-                    if err.code == "NoSuchObjectLockConfiguration" {
+                    if err.code == S3ErrorCode::NoSuchObjectLockConfiguration {
                         return Ok(IsObjectLegalHoldEnabledResponse {
                             headers: HeaderMap::new(),
                             region: region.clone(),
@@ -2361,31 +3208,22 @@ impl<'a> Client<'a> {
         })
     }
 
-    pub async fn set_bucket_quota(
-        &self,
-        args: &SetBucketQuotaArgs<'_>,
-    ) -> Result<SetBucketQuotaResponse, Error> {
-        let mut headers = Multimap::new();
-        if let Some(v) = &args.extra_headers {
-            merge(&mut headers, v);
-        }
-        let mut query_params = Multimap::new();
-        query_params.insert("bucket".into(), args.bucket_name.into());
-
+    pub async fn set_bucket_quota(
+        &self,
+        args: &SetBucketQuotaArgs<'_>,
+    ) -> Result<SetBucketQuotaResponse, Error> {
         let mut query_params = Multimap::new();
         query_params.insert("bucket".into(), args.bucket_name.into());
 
         let data = serde_json::to_string(&args.quota)?;
 
         let resp = self
-            .execute(
+            .execute_admin(
                 Method::PUT,
-                &"us-east-1".into(),
-                &mut headers,
+                "set-bucket-quota",
+                args.extra_headers,
                 &query_params,
-                "minio/admin/v3/set-bucket-quota".into(),
-                None,
-                Some(data.as_bytes()),
+                Some(Bytes::from(data)),
             )
             .await?;
 
@@ -2401,28 +3239,22 @@ impl<'a> Client<'a> {
         &self,
         args: &GetBucketQuotaArgs<'_>,
     ) -> Result<GetBucketQuotaResponse, Error> {
-        let mut headers = Multimap::new();
-        if let Some(v) = &args.extra_headers {
-            merge(&mut headers, v);
-        }
         let mut query_params = Multimap::new();
         query_params.insert("bucket".into(), args.bucket_name.into());
 
         let resp = self
-            .execute(
+            .execute_admin(
                 Method::GET,
-                &"us-east-1".into(),
-                &mut headers,
+                "get-bucket-quota",
+                args.extra_headers,
                 &query_params,
-                "minio/admin/v3/get-bucket-quota".into(),
-                None,
                 None,
             )
             .await?;
 
         let headers = resp.headers().clone();
-        let body = resp.bytes().await.unwrap().to_vec();
-        let quota: Quota = serde_json::from_str(&String::from_utf8(body).unwrap())?;
+        let body = resp.bytes().await?;
+        let quota: Quota = serde_json::from_slice(&body)?;
 
         Ok(GetBucketQuotaResponse {
             headers,
@@ -2448,25 +3280,12 @@ impl<'a> Client<'a> {
             merge(&mut headers, v);
         }
 
-        let mut query_params = Multimap::new();
-        if let Some(v) = &args.extra_query_params {
-            merge(&mut query_params, v);
-        }
-        if let Some(v) = args.prefix {
-            query_params.insert(String::from("prefix"), v.to_string());
-        }
-        if let Some(v) = args.suffix {
-            query_params.insert(String::from("suffix"), v.to_string());
-        }
-        if let Some(v) = &args.events {
-            for e in v.iter() {
-                query_params.insert(String::from("events"), e.to_string());
-            }
-        } else {
-            query_params.insert(String::from("events"), String::from("s3:ObjectCreated:*"));
-            query_params.insert(String::from("events"), String::from("s3:ObjectRemoved:*"));
-            query_params.insert(String::from("events"), String::from("s3:ObjectAccessed:*"));
-        }
+        let query_params = listen_bucket_notification_query_params(
+            args.extra_query_params,
+            args.prefix,
+            args.suffix,
+            &args.events,
+        );
 
         let mut resp = self
             .execute(
@@ -2520,6 +3339,39 @@ impl<'a> Client<'a> {
         ))
     }
 
+    pub fn listen_bucket_notification_stream(
+        &self,
+        args: &ListenBucketNotificationStreamArgs<'_>,
+    ) -> impl Stream<Item = Result<NotificationRecord, Error>> + 'static {
+        let mut headers = Multimap::new();
+        if let Some(v) = &args.extra_headers {
+            merge(&mut headers, v);
+        }
+
+        let state = NotificationStreamState {
+            client: self.clone(),
+            bucket: args.bucket.to_string(),
+            region: args.region.map(|v| v.to_string()),
+            headers,
+            query_params: listen_bucket_notification_query_params(
+                args.extra_query_params,
+                args.prefix,
+                args.suffix,
+                &args.events,
+            ),
+            resp: None,
+            buf: Vec::new(),
+            records: VecDeque::new(),
+            attempt: 0,
+            done: false,
+        };
+
+        futures_util::stream::unfold(state, |mut state| async move {
+            let item = state.next_record().await?;
+            Some((item, state))
+        })
+    }
+
     pub async fn list_objects_v1(
         &self,
         args: &ListObjectsV1Args<'_>,
@@ -2748,93 +3600,347 @@ impl<'a> Client<'a> {
         })
     }
 
-    pub async fn list_objects(&self, args: &ListObjectsArgs<'_>) -> Result<(), Error> {
-        let mut lov1_args = ListObjectsV1Args::new(args.bucket)?;
-        lov1_args.extra_headers = args.extra_headers;
-        lov1_args.extra_query_params = args.extra_query_params;
-        lov1_args.region = args.region;
-        if args.recursive {
-            lov1_args.delimiter = None;
-        } else {
-            lov1_args.delimiter = Some(args.delimiter.unwrap_or("/"));
-        }
-        lov1_args.encoding_type = match args.use_url_encoding_type {
+    fn list_objects_state<'a>(
+        args: &ListObjectsStreamArgs<'a>,
+    ) -> Result<ListObjectsState<'a>, Error> {
+        let delimiter = match args.recursive {
+            true => None,
+            false => Some(args.delimiter.unwrap_or("/")),
+        };
+        let encoding_type = match args.use_url_encoding_type {
             true => Some("url"),
             false => None,
         };
-        lov1_args.max_keys = args.max_keys;
-        lov1_args.prefix = args.prefix;
-        lov1_args.marker = args.marker.map(|x| x.to_string());
+
+        if args.include_versions {
+            let mut lov_args = ListObjectVersionsArgs::new(args.bucket)?;
+            lov_args.extra_headers = args.extra_headers;
+            lov_args.extra_query_params = args.extra_query_params;
+            lov_args.region = args.region;
+            lov_args.delimiter = delimiter;
+            lov_args.encoding_type = encoding_type;
+            lov_args.max_keys = args.max_keys;
+            lov_args.prefix = args.prefix;
+            lov_args.key_marker = args.key_marker.map(|x| x.to_string());
+            lov_args.version_id_marker = args.version_id_marker.map(|x| x.to_string());
+            return Ok(ListObjectsState::Versions(lov_args));
+        }
+
+        if args.use_api_v1 {
+            let mut lov1_args = ListObjectsV1Args::new(args.bucket)?;
+            lov1_args.extra_headers = args.extra_headers;
+            lov1_args.extra_query_params = args.extra_query_params;
+            lov1_args.region = args.region;
+            lov1_args.delimiter = delimiter;
+            lov1_args.encoding_type = encoding_type;
+            lov1_args.max_keys = args.max_keys;
+            lov1_args.prefix = args.prefix;
+            lov1_args.marker = args.marker.map(|x| x.to_string());
+            return Ok(ListObjectsState::V1(lov1_args));
+        }
 
         let mut lov2_args = ListObjectsV2Args::new(args.bucket)?;
         lov2_args.extra_headers = args.extra_headers;
         lov2_args.extra_query_params = args.extra_query_params;
         lov2_args.region = args.region;
-        if args.recursive {
-            lov2_args.delimiter = None;
-        } else {
-            lov2_args.delimiter = Some(args.delimiter.unwrap_or("/"));
-        }
-        lov2_args.encoding_type = match args.use_url_encoding_type {
-            true => Some("url"),
-            false => None,
-        };
+        lov2_args.delimiter = delimiter;
+        lov2_args.encoding_type = encoding_type;
         lov2_args.max_keys = args.max_keys;
         lov2_args.prefix = args.prefix;
         lov2_args.start_after = args.start_after.map(|x| x.to_string());
         lov2_args.continuation_token = args.continuation_token.map(|x| x.to_string());
         lov2_args.fetch_owner = args.fetch_owner;
         lov2_args.include_user_metadata = args.include_user_metadata;
+        Ok(ListObjectsState::V2(lov2_args))
+    }
 
-        let mut lov_args = ListObjectVersionsArgs::new(args.bucket)?;
-        lov_args.extra_headers = args.extra_headers;
-        lov_args.extra_query_params = args.extra_query_params;
-        lov_args.region = args.region;
-        if args.recursive {
-            lov_args.delimiter = None;
-        } else {
-            lov_args.delimiter = Some(args.delimiter.unwrap_or("/"));
-        }
-        lov_args.encoding_type = match args.use_url_encoding_type {
-            true => Some("url"),
-            false => None,
-        };
-        lov_args.max_keys = args.max_keys;
-        lov_args.prefix = args.prefix;
-        lov_args.key_marker = args.key_marker.map(|x| x.to_string());
-        lov_args.version_id_marker = args.version_id_marker.map(|x| x.to_string());
-
-        let mut stop = false;
-        while !stop {
-            if args.include_versions {
+    // Fetches one page and returns the state to fetch the next one, if any.
+    async fn list_objects_page<'a>(
+        &self,
+        state: ListObjectsState<'a>,
+    ) -> Result<(Vec<Item>, Option<ListObjectsState<'a>>), Error> {
+        match state {
+            ListObjectsState::Versions(mut lov_args) => {
                 let resp = self.list_object_versions(&lov_args).await?;
-                stop = !resp.is_truncated;
-                if resp.is_truncated {
-                    lov_args.key_marker = resp.next_key_marker;
-                    lov_args.version_id_marker = resp.next_version_id_marker;
+                if !resp.is_truncated {
+                    return Ok((resp.contents, None));
                 }
-                stop = stop || !(args.result_fn)(resp.contents);
-            } else if args.use_api_v1 {
+                lov_args.key_marker = resp.next_key_marker;
+                lov_args.version_id_marker = resp.next_version_id_marker;
+                Ok((resp.contents, Some(ListObjectsState::Versions(lov_args))))
+            }
+            ListObjectsState::V1(mut lov1_args) => {
                 let resp = self.list_objects_v1(&lov1_args).await?;
-                stop = !resp.is_truncated;
-                if resp.is_truncated {
-                    lov1_args.marker = resp.next_marker;
+                if !resp.is_truncated {
+                    return Ok((resp.contents, None));
                 }
-                stop = stop || !(args.result_fn)(resp.contents);
-            } else {
+                lov1_args.marker = resp.next_marker;
+                Ok((resp.contents, Some(ListObjectsState::V1(lov1_args))))
+            }
+            ListObjectsState::V2(mut lov2_args) => {
                 let resp = self.list_objects_v2(&lov2_args).await?;
-                stop = !resp.is_truncated;
-                if resp.is_truncated {
-                    lov2_args.start_after = resp.start_after;
-                    lov2_args.continuation_token = resp.next_continuation_token;
+                if !resp.is_truncated {
+                    return Ok((resp.contents, None));
                 }
-                stop = stop || !(args.result_fn)(resp.contents);
+                lov2_args.start_after = resp.start_after;
+                lov2_args.continuation_token = resp.next_continuation_token;
+                Ok((resp.contents, Some(ListObjectsState::V2(lov2_args))))
+            }
+        }
+    }
+
+    fn list_objects_pages<'a>(
+        &'a self,
+        args: &ListObjectsStreamArgs<'a>,
+    ) -> impl Stream<Item = Result<Vec<Item>, Error>> + 'a {
+        let state = Client::list_objects_state(args);
+        futures_util::stream::unfold(Some(state), move |state| async move {
+            match state? {
+                Ok(s) => match self.list_objects_page(s).await {
+                    Ok((items, next)) => Some((Ok(items), next.map(Ok))),
+                    Err(e) => Some((Err(e), None)),
+                },
+                Err(e) => Some((Err(e), None)),
+            }
+        })
+    }
+
+    pub async fn list_objects(&self, args: &ListObjectsArgs<'_>) -> Result<(), Error> {
+        let pages = self.list_objects_pages(&ListObjectsStreamArgs::from(args));
+        futures_util::pin_mut!(pages);
+        while let Some(items) = pages.next().await {
+            if !(args.result_fn)(items?) {
+                break;
             }
         }
 
         Ok(())
     }
 
+    pub fn list_objects_stream<'a>(
+        &'a self,
+        args: &ListObjectsStreamArgs<'a>,
+    ) -> impl Stream<Item = Result<Item, Error>> + 'a {
+        self.list_objects_pages(args).flat_map(|page| {
+            futures_util::stream::iter(match page {
+                Ok(items) => items.into_iter().map(Ok).collect(),
+                Err(e) => vec![Err(e)],
+            })
+        })
+    }
+
+    pub async fn list_all_objects(
+        &self,
+        args: &ListObjectsStreamArgs<'_>,
+    ) -> Result<Vec<Item>, Error> {
+        let mut items: Vec<Item> = Vec::new();
+        let pages = self.list_objects_pages(args);
+        futures_util::pin_mut!(pages);
+        while let Some(page) = pages.next().await {
+            items.append(&mut page?);
+        }
+
+        Ok(items)
+    }
+
+    pub async fn list_multipart_uploads(
+        &self,
+        args: &ListMultipartUploadsArgs<'_>,
+    ) -> Result<ListMultipartUploadsResponse, Error> {
+        let region = self.get_region(args.bucket, args.region).await?;
+
+        let mut headers = Multimap::new();
+        if let Some(v) = &args.extra_headers {
+            merge(&mut headers, v);
+        }
+
+        let mut query_params = Multimap::new();
+        if let Some(v) = &args.extra_query_params {
+            merge(&mut query_params, v);
+        }
+        query_params.insert(String::from("uploads"), String::new());
+        query_params.insert(
+            String::from("delimiter"),
+            args.delimiter.unwrap_or("").to_string(),
+        );
+        query_params.insert(
+            String::from("max-uploads"),
+            args.max_uploads.unwrap_or(1000).to_string(),
+        );
+        query_params.insert(
+            String::from("prefix"),
+            args.prefix.unwrap_or("").to_string(),
+        );
+        if let Some(v) = args.encoding_type {
+            query_params.insert(String::from("encoding-type"), v.to_string());
+        }
+        if let Some(v) = &args.key_marker {
+            query_params.insert(String::from("key-marker"), v.to_string());
+        }
+        if let Some(v) = &args.upload_id_marker {
+            query_params.insert(String::from("upload-id-marker"), v.to_string());
+        }
+
+        let resp = self
+            .execute(
+                Method::GET,
+                &region,
+                &mut headers,
+                &query_params,
+                Some(args.bucket),
+                None,
+                None,
+            )
+            .await?;
+        let header_map = resp.headers().clone();
+        let body = resp.bytes().await?;
+        let mut root = Element::parse(body.reader())?;
+
+        let encoding_type = get_option_text(&root, "EncodingType");
+        let mut uploads: Vec<MultipartUpload> = Vec::new();
+        while let Some(upload) = root.take_child("Upload") {
+            let (owner_id, owner_name) = match upload.get_child("Owner") {
+                Some(v) => (get_option_text(v, "ID"), get_option_text(v, "DisplayName")),
+                None => (None, None),
+            };
+            let (initiator_id, initiator_name) = match upload.get_child("Initiator") {
+                Some(v) => (get_option_text(v, "ID"), get_option_text(v, "DisplayName")),
+                None => (None, None),
+            };
+            uploads.push(MultipartUpload {
+                object_name: url_decode(&encoding_type, Some(get_text(&upload, "Key")?))?.unwrap(),
+                upload_id: get_text(&upload, "UploadId")?,
+                initiated: match get_option_text(&upload, "Initiated") {
+                    Some(v) => Some(from_iso8601utc(&v)?),
+                    None => None,
+                },
+                storage_class: get_option_text(&upload, "StorageClass"),
+                owner_id,
+                owner_name,
+                initiator_id,
+                initiator_name,
+            });
+        }
+        let mut common_prefixes: Vec<String> = Vec::new();
+        while let Some(prefix) = root.take_child("CommonPrefixes") {
+            common_prefixes
+                .push(url_decode(&encoding_type, Some(get_text(&prefix, "Prefix")?))?.unwrap());
+        }
+
+        Ok(ListMultipartUploadsResponse {
+            headers: header_map,
+            region,
+            bucket_name: args.bucket.to_string(),
+            prefix: url_decode(&encoding_type, get_option_text(&root, "Prefix"))?,
+            delimiter: get_option_text(&root, "Delimiter"),
+            key_marker: url_decode(&encoding_type, get_option_text(&root, "KeyMarker"))?,
+            upload_id_marker: get_option_text(&root, "UploadIdMarker"),
+            next_key_marker: url_decode(&encoding_type, get_option_text(&root, "NextKeyMarker"))?,
+            next_upload_id_marker: get_option_text(&root, "NextUploadIdMarker"),
+            max_uploads: parse_option_u16(&root, "MaxUploads")?,
+            is_truncated: get_default_text(&root, "IsTruncated").to_lowercase() == "true",
+            encoding_type,
+            uploads,
+            common_prefixes,
+        })
+    }
+
+    pub async fn list_parts(&self, args: &ListPartsArgs<'_>) -> Result<ListPartsResponse, Error> {
+        let region = self.get_region(args.bucket, args.region).await?;
+
+        let mut headers = Multimap::new();
+        if let Some(v) = &args.extra_headers {
+            merge(&mut headers, v);
+        }
+
+        let mut query_params = Multimap::new();
+        if let Some(v) = &args.extra_query_params {
+            merge(&mut query_params, v);
+        }
+        query_params.insert(String::from("uploadId"), args.upload_id.to_string());
+        query_params.insert(
+            String::from("max-parts"),
+            args.max_parts.unwrap_or(1000).to_string(),
+        );
+        if let Some(v) = args.part_number_marker {
+            query_params.insert(String::from("part-number-marker"), v.to_string());
+        }
+
+        let resp = self
+            .execute(
+                Method::GET,
+                &region,
+                &mut headers,
+                &query_params,
+                Some(args.bucket),
+                Some(args.object),
+                None,
+            )
+            .await?;
+        let header_map = resp.headers().clone();
+        let body = resp.bytes().await?;
+        let mut root = Element::parse(body.reader())?;
+
+        let mut parts: Vec<PartInfo> = Vec::new();
+        while let Some(part) = root.take_child("Part") {
+            parts.push(PartInfo {
+                number: get_text(&part, "PartNumber")?.parse::<u16>()?,
+                etag: get_text(&part, "ETag")?,
+                size: get_text(&part, "Size")?.parse::<usize>()?,
+                last_modified: match get_option_text(&part, "LastModified") {
+                    Some(v) => Some(from_iso8601utc(&v)?),
+                    None => None,
+                },
+            });
+        }
+
+        Ok(ListPartsResponse {
+            headers: header_map,
+            region,
+            bucket_name: args.bucket.to_string(),
+            object_name: args.object.to_string(),
+            upload_id: args.upload_id.to_string(),
+            storage_class: get_option_text(&root, "StorageClass"),
+            part_number_marker: parse_option_u16(&root, "PartNumberMarker")?,
+            next_part_number_marker: parse_option_u16(&root, "NextPartNumberMarker")?,
+            max_parts: parse_option_u16(&root, "MaxParts")?,
+            is_truncated: get_default_text(&root, "IsTruncated").to_lowercase() == "true",
+            parts,
+        })
+    }
+
+    async fn list_all_parts(
+        &self,
+        bucket_name: &str,
+        object_name: &str,
+        upload_id: &str,
+        region: Option<&str>,
+    ) -> Result<Vec<PartInfo>, Error> {
+        let mut parts: Vec<PartInfo> = Vec::new();
+        let mut lpargs = ListPartsArgs::new(bucket_name, object_name, upload_id)?;
+        lpargs.region = region;
+        loop {
+            let mut resp = self.list_parts(&lpargs).await?;
+            parts.append(&mut resp.parts);
+            if !resp.is_truncated {
+                break;
+            }
+            // Guard against servers not advancing the marker, which would loop forever.
+            match resp.next_part_number_marker {
+                Some(v) if lpargs.part_number_marker < Some(v) => {
+                    lpargs.part_number_marker = Some(v)
+                }
+                _ => {
+                    return Err(Error::XmlError(String::from(
+                        "truncated ListParts response without advancing NextPartNumberMarker",
+                    )))
+                }
+            }
+        }
+
+        Ok(parts)
+    }
+
     pub async fn make_bucket(
         &self,
         args: &MakeBucketArgs<'_>,
@@ -2876,7 +3982,7 @@ impl<'a> Client<'a> {
 
         let body = match data.is_empty() {
             true => None,
-            false => Some(data.as_bytes()),
+            false => Some(Bytes::from(data)),
         };
 
         let resp = self
@@ -2900,8 +4006,8 @@ impl<'a> Client<'a> {
         })
     }
 
-    fn read_part(
-        reader: &mut dyn std::io::Read,
+    async fn read_part(
+        reader: &mut ObjectReader<'_>,
         buf: &mut [u8],
         size: usize,
     ) -> Result<usize, Error> {
@@ -2909,7 +4015,7 @@ impl<'a> Client<'a> {
         let mut i = 0_usize;
         let mut stop = false;
         while !stop {
-            let br = reader.read(&mut buf[i..size])?;
+            let br = reader.read(&mut buf[i..size]).await?;
             bytes_read += br;
             stop = (br == 0) || (br == size - i);
             i += br;
@@ -2921,7 +4027,6 @@ impl<'a> Client<'a> {
     async fn do_put_object(
         &self,
         args: &mut PutObjectArgs<'_>,
-        buf: &mut [u8],
         upload_id: &mut String,
     ) -> Result<PutObjectResponse, Error> {
         let mut headers = args.get_headers();
@@ -2941,12 +4046,49 @@ impl<'a> Client<'a> {
         let mut stop = false;
         let mut one_byte: Vec<u8> = Vec::new();
         let mut parts: Vec<Part> = Vec::new();
-        let object_size = &args.object_size.unwrap();
+        let object_size = args.object_size.unwrap_or_default();
         let mut part_size = args.part_size;
         let mut part_count = args.part_count;
 
+        let bufsize = args.part_buffer_size();
+        let max_uploads = match args.max_buffer_size {
+            Some(v) => std::cmp::min(args.parallel_uploads, v / bufsize),
+            None => args.parallel_uploads,
+        }
+        .max(1);
+
+        let ssec_headers = match args.sse {
+            Some(v) => match v.as_any().downcast_ref::<SseCustomerKey>() {
+                Some(_) => v.headers(),
+                _ => Multimap::new(),
+            },
+            _ => Multimap::new(),
+        };
+
+        // Parts already uploaded to a resumed upload are reused if their content matches.
+        let existing_parts: HashMap<u16, PartInfo> = match args.upload_id {
+            Some(v) => self
+                .list_all_parts(args.bucket, args.object, v, args.region)
+                .await?
+                .into_iter()
+                .map(|p| (p.number, p))
+                .collect(),
+            None => HashMap::new(),
+        };
+
+        // Dropping the set on error aborts parts still being uploaded.
+        let mut uploads: JoinSet<Result<Part, Error>> = JoinSet::new();
+
         while !stop {
+            // Wait for a free slot before buffering another part.
+            if uploads.len() >= max_uploads {
+                if let Some(v) = uploads.join_next().await {
+                    parts.push(Client::join_part(v)?);
+                }
+            }
+
             part_number += 1;
+            let mut buf = vec![0_u8; bufsize];
             let mut bytes_read = 0_usize;
             if args.part_count > 0 {
                 if part_number == args.part_count {
@@ -2954,7 +4096,7 @@ impl<'a> Client<'a> {
                     stop = true;
                 }
 
-                bytes_read = Client::read_part(&mut args.stream, buf, part_size)?;
+                bytes_read = Client::read_part(&mut args.stream, &mut buf, part_size).await?;
                 if bytes_read != part_size {
                     return Err(Error::InsufficientData(part_size, bytes_read));
                 }
@@ -2967,10 +4109,10 @@ impl<'a> Client<'a> {
                         bytes_read = 1;
                         &mut buf[1..]
                     }
-                    false => buf,
+                    false => &mut buf[..],
                 };
 
-                let n = Client::read_part(&mut args.stream, newbuf, size)?;
+                let n = Client::read_part(&mut args.stream, newbuf, size).await?;
                 bytes_read += n;
 
                 // If bytes read is less than or equals to part size, then we have reached last part.
@@ -2979,18 +4121,20 @@ impl<'a> Client<'a> {
                     part_size = bytes_read;
                     stop = true;
                 } else {
-                    one_byte.push(buf[part_size + 1]);
+                    one_byte.push(buf[part_size]);
                 }
             }
 
-            let data = &buf[0..part_size];
+            buf.truncate(part_size);
+            let buf = Bytes::from(buf);
             uploaded_size += part_size;
 
-            if part_count == 1_i16 {
-                let mut poaargs = PutObjectApiArgs::new(args.bucket, args.object, data)?;
+            if part_count == 1_i16 && upload_id.is_empty() {
+                let mut poaargs = PutObjectApiArgs::new(args.bucket, args.object, buf)?;
                 poaargs.extra_query_params = args.extra_query_params;
                 poaargs.region = args.region;
                 poaargs.headers = Some(&headers);
+                poaargs.checksum = args.checksum;
 
                 return self.put_object_api(&poaargs).await;
             }
@@ -3000,43 +4144,68 @@ impl<'a> Client<'a> {
                 cmuargs.extra_query_params = args.extra_query_params;
                 cmuargs.region = args.region;
                 cmuargs.headers = Some(&headers);
+                cmuargs.checksum = args.checksum;
 
                 let resp = self.create_multipart_upload(&cmuargs).await?;
                 upload_id.push_str(&resp.upload_id);
             }
 
-            let mut upargs = UploadPartArgs::new(
-                args.bucket,
-                args.object,
-                upload_id,
-                part_number as u16,
-                data,
-            )?;
-            upargs.region = args.region;
-
-            let ssec_headers = match args.sse {
-                Some(v) => match v.as_any().downcast_ref::<SseCustomerKey>() {
-                    Some(_) => v.headers(),
-                    _ => Multimap::new(),
-                },
-                _ => Multimap::new(),
-            };
-            upargs.headers = Some(&ssec_headers);
+            if let Some(p) = existing_parts.get(&(part_number as u16)) {
+                if p.size == buf.len()
+                    && p.etag.trim_matches('"') == format!("{:x}", md5::compute(&buf))
+                {
+                    parts.push(Part {
+                        number: p.number,
+                        etag: p.etag.clone(),
+                        checksum: args.checksum.map(|v| v.checksum(&buf)),
+                    });
+                    continue;
+                }
+            }
 
-            let resp = self.upload_part(&upargs).await?;
-            parts.push(Part {
-                number: part_number as u16,
-                etag: resp.etag.clone(),
+            let client = self.clone();
+            let bucket = args.bucket.to_string();
+            let object = args.object.to_string();
+            let region = args.region.map(|v| v.to_string());
+            let upload_id = upload_id.clone();
+            let ssec_headers = ssec_headers.clone();
+            let number = part_number as u16;
+            let checksum = args.checksum;
+            uploads.spawn(async move {
+                let mut upargs = UploadPartArgs::new(&bucket, &object, &upload_id, number, buf)?;
+                upargs.region = region.as_deref();
+                upargs.headers = Some(&ssec_headers);
+                upargs.checksum = checksum;
+
+                let resp = client.upload_part(&upargs).await?;
+                Ok(Part {
+                    number,
+                    etag: resp.etag,
+                    checksum: resp.checksum.map(|v| v.value),
+                })
             });
         }
 
+        while let Some(v) = uploads.join_next().await {
+            parts.push(Client::join_part(v)?);
+        }
+        parts.sort_by_key(|v| v.number);
+
         let mut cmuargs =
             CompleteMultipartUploadArgs::new(args.bucket, args.object, upload_id, &parts)?;
         cmuargs.region = args.region;
+        cmuargs.checksum = args.checksum;
 
         self.complete_multipart_upload(&cmuargs).await
     }
 
+    fn join_part(res: Result<Result<Part, Error>, JoinError>) -> Result<Part, Error> {
+        match res {
+            Ok(v) => v,
+            Err(e) => std::panic::resume_unwind(e.into_panic()),
+        }
+    }
+
     pub async fn put_object(
         &self,
         args: &mut PutObjectArgs<'_>,
@@ -3047,20 +4216,22 @@ impl<'a> Client<'a> {
             }
         }
 
-        let bufsize = match args.part_count > 0 {
-            true => args.part_size,
-            false => args.part_size + 1,
-        };
-        let mut buf = vec![0_u8; bufsize];
-
-        let mut upload_id = String::new();
-        let res = self.do_put_object(args, &mut buf, &mut upload_id).await;
+        if let Some(v) = args.max_buffer_size {
+            if v < args.part_buffer_size() {
+                return Err(Error::InvalidMaxBufferSize(v, args.part_buffer_size()));
+            }
+        }
 
-        std::mem::drop(buf);
+        let mut upload_id = args.upload_id.unwrap_or_default().to_string();
+        let res = self.do_put_object(args, &mut upload_id).await;
 
-        if res.is_err() && !upload_id.is_empty() {
-            let amuargs = &AbortMultipartUploadArgs::new(args.bucket, args.object, &upload_id)?;
-            self.abort_multipart_upload(amuargs).await?;
+        // Resumed uploads are left in place so that they can be resumed again. Failure of abort
+        // must not hide the original error.
+        if res.is_err() && !upload_id.is_empty() && args.upload_id.is_none() {
+            if let Ok(amuargs) = AbortMultipartUploadArgs::new(args.bucket, args.object, &upload_id)
+            {
+                let _ = self.abort_multipart_upload(&amuargs).await;
+            }
         }
 
         res
@@ -3073,6 +4244,17 @@ impl<'a> Client<'a> {
         let region = self.get_region(args.bucket, args.region).await?;
 
         let mut headers = args.get_headers();
+        let checksum = args.checksum.map(|a| Checksum {
+            algorithm: a,
+            value: a.checksum(&args.data),
+        });
+        if let Some(v) = &checksum {
+            headers.insert(
+                String::from("x-amz-sdk-checksum-algorithm"),
+                v.algorithm.as_str().to_string(),
+            );
+            headers.insert(v.algorithm.header_name(), v.value.clone());
+        }
 
         let mut query_params = Multimap::new();
         if let Some(v) = &args.extra_query_params {
@@ -3090,7 +4272,7 @@ impl<'a> Client<'a> {
                 &query_params,
                 Some(args.bucket),
                 Some(args.object),
-                Some(args.data),
+                Some(args.data.clone()),
             )
             .await?;
         let header_map = resp.headers();
@@ -3108,6 +4290,7 @@ impl<'a> Client<'a> {
                 Some(v) => Some(v.to_str()?.to_string()),
                 None => None,
             },
+            checksum: Checksum::from_headers(header_map).or(checksum),
         })
     }
 
@@ -3240,7 +4423,7 @@ impl<'a> Client<'a> {
                 &query_params,
                 Some(args.bucket),
                 None,
-                Some(b),
+                Some(Bytes::from(data)),
             )
             .await?;
         let header_map = resp.headers().clone();
@@ -3347,7 +4530,7 @@ impl<'a> Client<'a> {
                 &query_params,
                 Some(args.bucket),
                 None,
-                Some(args.config.to_xml().as_bytes()),
+                Some(Bytes::from(args.config.to_xml())),
             )
             .await?;
 
@@ -3383,7 +4566,7 @@ impl<'a> Client<'a> {
                 &query_params,
                 Some(args.bucket),
                 None,
-                Some(args.config.to_xml().as_bytes()),
+                Some(Bytes::from(args.config.to_xml())),
             )
             .await?;
 
@@ -3419,7 +4602,7 @@ impl<'a> Client<'a> {
                 &query_params,
                 Some(args.bucket),
                 None,
-                Some(args.config.to_xml().as_bytes()),
+                Some(Bytes::from(args.config.to_xml())),
             )
             .await?;
 
@@ -3434,6 +4617,8 @@ impl<'a> Client<'a> {
         &self,
         args: &SetBucketPolicyArgs<'_>,
     ) -> Result<SetBucketPolicyResponse, Error> {
+        args.config.validate(args.bucket)?;
+
         let region = self.get_region(args.bucket, args.region).await?;
 
         let mut headers = Multimap::new();
@@ -3455,7 +4640,7 @@ impl<'a> Client<'a> {
                 &query_params,
                 Some(args.bucket),
                 None,
-                Some(args.config.as_bytes()),
+                Some(Bytes::from(args.config.to_json()?)),
             )
             .await?;
 
@@ -3466,6 +4651,36 @@ impl<'a> Client<'a> {
         })
     }
 
+    /// Sets anonymous access to objects under given prefix by updating canned statements of
+    /// bucket policy; the policy is removed once no statement is left.
+    pub async fn set_anonymous_access(
+        &self,
+        args: &SetAnonymousAccessArgs<'_>,
+    ) -> Result<SetAnonymousAccessResponse, Error> {
+        let bucket_args = BucketArgs {
+            extra_headers: args.extra_headers,
+            extra_query_params: args.extra_query_params,
+            region: args.region,
+            bucket: args.bucket,
+        };
+
+        let mut config = self.get_bucket_policy(&bucket_args).await?.config;
+        config.set_access(args.bucket, args.prefix, args.access);
+
+        if config.is_empty() {
+            return self.delete_bucket_policy(&bucket_args).await;
+        }
+
+        self.set_bucket_policy(&SetBucketPolicyArgs {
+            extra_headers: args.extra_headers,
+            extra_query_params: args.extra_query_params,
+            region: args.region,
+            bucket: args.bucket,
+            config: &config,
+        })
+        .await
+    }
+
     pub async fn set_bucket_replication(
         &self,
         args: &SetBucketReplicationArgs<'_>,
@@ -3491,7 +4706,7 @@ impl<'a> Client<'a> {
                 &query_params,
                 Some(args.bucket),
                 None,
-                Some(args.config.to_xml().as_bytes()),
+                Some(Bytes::from(args.config.to_xml())),
             )
             .await?;
 
@@ -3544,7 +4759,7 @@ impl<'a> Client<'a> {
                 &query_params,
                 Some(args.bucket),
                 None,
-                Some(data.as_bytes()),
+                Some(Bytes::from(data)),
             )
             .await?;
 
@@ -3597,7 +4812,7 @@ impl<'a> Client<'a> {
                 &query_params,
                 Some(args.bucket),
                 None,
-                Some(data.as_bytes()),
+                Some(Bytes::from(data)),
             )
             .await?;
 
@@ -3633,7 +4848,7 @@ impl<'a> Client<'a> {
                 &query_params,
                 Some(args.bucket),
                 None,
-                Some(args.config.to_xml().as_bytes()),
+                Some(Bytes::from(args.config.to_xml())),
             )
             .await?;
 
@@ -3693,7 +4908,7 @@ impl<'a> Client<'a> {
                 &query_params,
                 Some(args.bucket),
                 Some(args.object),
-                Some(data.as_bytes()),
+                Some(Bytes::from(data)),
             )
             .await?;
 
@@ -3751,7 +4966,7 @@ impl<'a> Client<'a> {
                 &query_params,
                 Some(args.bucket),
                 Some(args.object),
-                Some(data.as_bytes()),
+                Some(Bytes::from(data)),
             )
             .await?;
 
@@ -3798,7 +5013,7 @@ impl<'a> Client<'a> {
                 &query_params,
                 Some(args.bucket),
                 Some(args.object),
-                Some(b),
+                Some(Bytes::from(data)),
             )
             .await?,
             &region,
@@ -3850,7 +5065,7 @@ impl<'a> Client<'a> {
         &self,
         args: &UploadObjectArgs<'_>,
     ) -> Result<UploadObjectResponse, Error> {
-        let mut file = File::open(args.filename)?;
+        let mut file = tokio::fs::File::open(args.filename).await?;
 
         self.put_object(&mut PutObjectArgs {
             extra_headers: args.extra_headers,
@@ -3868,7 +5083,11 @@ impl<'a> Client<'a> {
             part_size: args.part_size,
             part_count: args.part_count,
             content_type: args.content_type,
-            stream: &mut file,
+            parallel_uploads: args.parallel_uploads,
+            max_buffer_size: args.max_buffer_size,
+            upload_id: args.upload_id,
+            checksum: args.checksum,
+            stream: ObjectReader::AsyncRead(&mut file),
         })
         .await
     }
@@ -3881,7 +5100,7 @@ impl<'a> Client<'a> {
         query_params.insert(String::from("partNumber"), args.part_number.to_string());
         query_params.insert(String::from("uploadId"), args.upload_id.to_string());
 
-        let mut poa_args = PutObjectApiArgs::new(args.bucket, args.object, args.data)?;
+        let mut poa_args = PutObjectApiArgs::new(args.bucket, args.object, args.data.clone())?;
         poa_args.query_params = Some(&query_params);
 
         poa_args.extra_headers = args.extra_headers;
@@ -3893,6 +5112,7 @@ impl<'a> Client<'a> {
         poa_args.tags = args.tags;
         poa_args.retention = args.retention;
         poa_args.legal_hold = args.legal_hold;
+        poa_args.checksum = args.checksum;
 
         self.put_object_api(&poa_args).await
     }
@@ -3938,6 +5158,7 @@ impl<'a> Client<'a> {
             location: region.clone(),
             etag: get_text(&root, "ETag")?.trim_matches('"').to_string(),
             version_id: None,
+            checksum: None,
         })
     }
 }
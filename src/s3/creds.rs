@@ -13,22 +13,78 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-#[derive(Clone, Debug, Default)]
+use crate::s3::error::{Error, ErrorResponse, S3ErrorCode};
+use crate::s3::http::BaseUrl;
+use crate::s3::signer::sign_v4_sts;
+use crate::s3::utils::{
+    from_iso8601utc, get_default_text, get_option_text, get_text, sha256_hash, to_amz_date,
+    to_query_string, utc_now, Multimap, UtcTime,
+};
+use async_trait::async_trait;
+use bytes::{Buf, Bytes};
+use hyper::http::{HeaderMap, Method};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tokio::sync::Mutex;
+use xmltree::Element;
+
+// Placeholder of secrets in debug output; credentials end up in logged errors otherwise.
+const REDACTED: &str = "**REDACTED**";
+
+#[derive(Clone, Default)]
 pub struct Credentials {
     pub access_key: String,
     pub secret_key: String,
     pub session_token: Option<String>,
+    pub expiration: Option<UtcTime>,
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("access_key", &self.access_key)
+            .field("secret_key", &REDACTED)
+            .field(
+                "session_token",
+                &self.session_token.as_ref().map(|_| REDACTED),
+            )
+            .field("expiration", &self.expiration)
+            .finish()
+    }
+}
+
+impl Credentials {
+    pub fn is_expired(&self) -> bool {
+        self.expires_within(chrono::Duration::zero())
+    }
+
+    pub fn expires_within(&self, window: chrono::Duration) -> bool {
+        self.expiration.is_some_and(|v| v - window <= utc_now())
+    }
 }
 
+#[async_trait]
 pub trait Provider: std::fmt::Debug {
-    fn fetch(&self) -> Credentials;
+    async fn fetch(&self) -> Result<Credentials, Error>;
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct StaticProvider {
     creds: Credentials,
 }
 
+impl fmt::Debug for StaticProvider {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StaticProvider")
+            .field("access_key", &self.creds.access_key)
+            .finish_non_exhaustive()
+    }
+}
+
 impl StaticProvider {
     pub fn new(access_key: &str, secret_key: &str, session_token: Option<&str>) -> StaticProvider {
         StaticProvider {
@@ -36,13 +92,794 @@ impl StaticProvider {
                 access_key: access_key.to_string(),
                 secret_key: secret_key.to_string(),
                 session_token: session_token.map(|v| v.to_string()),
+                expiration: None,
             },
         }
     }
 }
 
+#[async_trait]
 impl Provider for StaticProvider {
-    fn fetch(&self) -> Credentials {
-        self.creds.clone()
+    async fn fetch(&self) -> Result<Credentials, Error> {
+        Ok(self.creds.clone())
+    }
+}
+
+fn env_value(keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .filter_map(|k| std::env::var(k).ok())
+        .find(|v| !v.is_empty())
+}
+
+fn home_dir() -> PathBuf {
+    PathBuf::from(env_value(&["HOME", "USERPROFILE"]).unwrap_or_default())
+}
+
+// Wraps errors from credential lookup so callers can tell them apart from
+// errors of the S3 request itself.
+pub(crate) fn credentials_error(e: Error) -> Error {
+    match e {
+        Error::CredentialsError(_) => e,
+        _ => Error::CredentialsError(e.to_string()),
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct EnvProvider {}
+
+impl EnvProvider {
+    pub fn new() -> EnvProvider {
+        EnvProvider {}
+    }
+}
+
+#[async_trait]
+impl Provider for EnvProvider {
+    async fn fetch(&self) -> Result<Credentials, Error> {
+        // MinIO specific variables take precedence over AWS ones.
+        if let (Some(access_key), Some(secret_key)) = (
+            env_value(&["MINIO_ROOT_USER", "MINIO_ACCESS_KEY"]),
+            env_value(&["MINIO_ROOT_PASSWORD", "MINIO_SECRET_KEY"]),
+        ) {
+            return Ok(Credentials {
+                access_key,
+                secret_key,
+                session_token: None,
+                expiration: None,
+            });
+        }
+
+        if let (Some(access_key), Some(secret_key)) = (
+            env_value(&["AWS_ACCESS_KEY_ID", "AWS_ACCESS_KEY"]),
+            env_value(&["AWS_SECRET_ACCESS_KEY", "AWS_SECRET_KEY"]),
+        ) {
+            return Ok(Credentials {
+                access_key,
+                secret_key,
+                session_token: env_value(&["AWS_SESSION_TOKEN"]),
+                expiration: None,
+            });
+        }
+
+        Err(Error::CredentialsError(String::from(
+            "access key and secret key are not set in environment",
+        )))
+    }
+}
+
+#[derive(Default)]
+pub struct AwsConfigProvider {
+    filename: Option<PathBuf>,
+    profile: Option<String>,
+    creds: OnceLock<Credentials>,
+}
+
+impl fmt::Debug for AwsConfigProvider {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AwsConfigProvider")
+            .field("filename", &self.filename)
+            .field("profile", &self.profile)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AwsConfigProvider {
+    pub fn new(filename: Option<&str>, profile: Option<&str>) -> AwsConfigProvider {
+        AwsConfigProvider {
+            filename: filename.map(PathBuf::from),
+            profile: profile.map(String::from),
+            creds: OnceLock::new(),
+        }
+    }
+
+    fn read(&self) -> Result<Credentials, Error> {
+        let filename = match &self.filename {
+            Some(v) => v.clone(),
+            None => match env_value(&["AWS_SHARED_CREDENTIALS_FILE"]) {
+                Some(v) => PathBuf::from(v),
+                None => home_dir().join(".aws").join("credentials"),
+            },
+        };
+        let profile = match &self.profile {
+            Some(v) => v.clone(),
+            None => env_value(&["AWS_PROFILE"]).unwrap_or(String::from("default")),
+        };
+
+        let content = std::fs::read_to_string(&filename)?;
+        let mut section = String::new();
+        let mut values: HashMap<String, String> = HashMap::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                section = line[1..line.len() - 1].trim().to_string();
+                continue;
+            }
+
+            if section != profile {
+                continue;
+            }
+
+            if let Some((key, value)) = line.split_once('=') {
+                values.insert(key.trim().to_lowercase(), value.trim().to_string());
+            }
+        }
+
+        match (
+            values.remove("aws_access_key_id"),
+            values.remove("aws_secret_access_key"),
+        ) {
+            (Some(access_key), Some(secret_key)) => Ok(Credentials {
+                access_key,
+                secret_key,
+                session_token: values.remove("aws_session_token"),
+                expiration: None,
+            }),
+            _ => Err(Error::CredentialsError(format!(
+                "credentials for profile {} not found in {}",
+                profile,
+                filename.display()
+            ))),
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for AwsConfigProvider {
+    async fn fetch(&self) -> Result<Credentials, Error> {
+        if let Some(v) = self.creds.get() {
+            return Ok(v.clone());
+        }
+
+        match self.read() {
+            Ok(v) => Ok(self.creds.get_or_init(|| v).clone()),
+            Err(e) => Err(credentials_error(e)),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct McAlias {
+    access_key: String,
+    secret_key: String,
+    session_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct McConfig {
+    // "hosts" is used by config.json older than version 10.
+    #[serde(alias = "hosts")]
+    aliases: HashMap<String, McAlias>,
+}
+
+#[derive(Default)]
+pub struct MinioClientConfigProvider {
+    filename: Option<PathBuf>,
+    alias: Option<String>,
+    creds: OnceLock<Credentials>,
+}
+
+impl fmt::Debug for MinioClientConfigProvider {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MinioClientConfigProvider")
+            .field("filename", &self.filename)
+            .field("alias", &self.alias)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MinioClientConfigProvider {
+    pub fn new(filename: Option<&str>, alias: Option<&str>) -> MinioClientConfigProvider {
+        MinioClientConfigProvider {
+            filename: filename.map(PathBuf::from),
+            alias: alias.map(String::from),
+            creds: OnceLock::new(),
+        }
+    }
+
+    fn read(&self) -> Result<Credentials, Error> {
+        let filename = match &self.filename {
+            Some(v) => v.clone(),
+            None => match env_value(&["MINIO_SHARED_CREDENTIALS_FILE"]) {
+                Some(v) => PathBuf::from(v),
+                None => home_dir().join(".mc").join("config.json"),
+            },
+        };
+        let alias = match &self.alias {
+            Some(v) => v.clone(),
+            None => env_value(&["MINIO_ALIAS"]).unwrap_or(String::from("s3")),
+        };
+
+        let mut config: McConfig = serde_json::from_slice(&std::fs::read(&filename)?)?;
+        match config.aliases.remove(&alias) {
+            Some(v) => Ok(Credentials {
+                access_key: v.access_key,
+                secret_key: v.secret_key,
+                session_token: v.session_token.filter(|t| !t.is_empty()),
+                expiration: None,
+            }),
+            None => Err(Error::CredentialsError(format!(
+                "alias {} not found in {}",
+                alias,
+                filename.display()
+            ))),
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for MinioClientConfigProvider {
+    async fn fetch(&self) -> Result<Credentials, Error> {
+        if let Some(v) = self.creds.get() {
+            return Ok(v.clone());
+        }
+
+        match self.read() {
+            Ok(v) => Ok(self.creds.get_or_init(|| v).clone()),
+            Err(e) => Err(credentials_error(e)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ChainProvider {
+    providers: Vec<Box<dyn Provider + Send + Sync>>,
+}
+
+impl ChainProvider {
+    pub fn new(providers: Vec<Box<dyn Provider + Send + Sync>>) -> ChainProvider {
+        ChainProvider { providers }
+    }
+
+    // Same lookup order as MinIO Go and Python SDKs.
+    pub fn default_chain() -> ChainProvider {
+        ChainProvider::new(vec![
+            Box::new(EnvProvider::new()),
+            Box::new(AwsConfigProvider::new(None, None)),
+            Box::new(MinioClientConfigProvider::new(None, None)),
+        ])
+    }
+}
+
+#[async_trait]
+impl Provider for ChainProvider {
+    async fn fetch(&self) -> Result<Credentials, Error> {
+        let mut errors: Vec<String> = Vec::new();
+        for (i, provider) in self.providers.iter().enumerate() {
+            match provider.fetch().await {
+                Ok(creds) if !creds.access_key.is_empty() => return Ok(creds),
+                Ok(_) => errors.push(format!("provider {} returned empty access key", i + 1)),
+                Err(Error::CredentialsError(m)) => errors.push(m),
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+
+        Err(Error::CredentialsError(format!(
+            "no credentials found in chain; {}",
+            errors.join("; ")
+        )))
+    }
+}
+
+// Refresh STS credentials this many seconds before they expire.
+const STS_EXPIRY_WINDOW: i64 = 60;
+
+#[derive(Debug, Default)]
+struct StsCache {
+    creds: Mutex<Option<Credentials>>,
+}
+
+impl StsCache {
+    // Returns cached credentials or awaits refresh while holding the lock, so
+    // concurrent callers share a single STS call.
+    async fn fetch(
+        &self,
+        refresh: impl Future<Output = Result<Credentials, Error>>,
+    ) -> Result<Credentials, Error> {
+        let mut cached = self.creds.lock().await;
+        if let Some(creds) = cached.as_ref() {
+            if !creds.expires_within(chrono::Duration::seconds(STS_EXPIRY_WINDOW)) {
+                return Ok(creds.clone());
+            }
+        }
+
+        let creds = refresh.await.map_err(credentials_error)?;
+        *cached = Some(creds.clone());
+        Ok(creds)
+    }
+}
+
+fn parse_sts_credentials(body: &mut Bytes) -> Result<Credentials, Error> {
+    let root = Element::parse(body.reader())?;
+    let result = root
+        .children
+        .iter()
+        .filter_map(|v| v.as_element())
+        .find(|v| v.name.ends_with("Result"))
+        .ok_or(Error::XmlError(String::from("<*Result> tag not found")))?;
+    let creds = result
+        .get_child("Credentials")
+        .ok_or(Error::XmlError(String::from("<Credentials> tag not found")))?;
+
+    Ok(Credentials {
+        access_key: get_text(creds, "AccessKeyId")?,
+        secret_key: get_text(creds, "SecretAccessKey")?,
+        session_token: get_option_text(creds, "SessionToken").filter(|v| !v.is_empty()),
+        expiration: Some(from_iso8601utc(&get_text(creds, "Expiration")?)?),
+    })
+}
+
+fn parse_sts_error(body: &mut Bytes, status_code: u16, headers: HeaderMap) -> Error {
+    let root = match Element::parse(body.reader()) {
+        Ok(v) => v,
+        Err(_) => return Error::ServerError(status_code),
+    };
+    let error = root.get_child("Error").unwrap_or(&root);
+
+    Error::S3Error(ErrorResponse {
+        code: S3ErrorCode::from(get_default_text(error, "Code")),
+        message: get_default_text(error, "Message"),
+        resource: String::new(),
+        request_id: get_default_text(&root, "RequestId"),
+        host_id: String::new(),
+        bucket_name: String::new(),
+        object_name: String::new(),
+        status_code,
+        headers,
+    })
+}
+
+// Posts form encoded STS parameters, signing the request when credentials are
+// given as AssumeRole requires.
+async fn sts_request(
+    http_client: &reqwest::Client,
+    sts_endpoint: &BaseUrl,
+    region: &str,
+    params: &Multimap,
+    creds: Option<(&str, &str)>,
+) -> Result<Credentials, Error> {
+    let region = match region.is_empty() {
+        true => String::from("us-east-1"),
+        false => region.to_string(),
+    };
+    let url = sts_endpoint.build_url(&Method::POST, &region, &Multimap::new(), None, None)?;
+    let body = to_query_string(params);
+
+    let mut headers = Multimap::new();
+    headers.insert(String::from("Host"), url.host_header_value());
+    headers.insert(
+        String::from("Content-Type"),
+        String::from("application/x-www-form-urlencoded"),
+    );
+    if let Some((access_key, secret_key)) = creds {
+        let date = utc_now();
+        let sha256 = sha256_hash(body.as_bytes());
+        headers.insert(String::from("x-amz-date"), to_amz_date(date));
+        headers.insert(String::from("x-amz-content-sha256"), sha256.clone());
+        sign_v4_sts(
+            &Method::POST,
+            &url.path,
+            &region,
+            &mut headers,
+            &Multimap::new(),
+            access_key,
+            secret_key,
+            &sha256,
+            date,
+        );
+    }
+
+    let mut req = http_client.post(url.to_string());
+    for (key, values) in headers.iter_all() {
+        for value in values {
+            req = req.header(key, value);
+        }
+    }
+
+    let resp = req.body(body).send().await?;
+    let status_code = resp.status().as_u16();
+    let success = resp.status().is_success();
+    let headers = resp.headers().clone();
+    let mut body = resp.bytes().await?;
+    match success {
+        true => parse_sts_credentials(&mut body),
+        false => Err(parse_sts_error(&mut body, status_code, headers)),
+    }
+}
+
+fn sts_params(action: &str, duration_seconds: Option<u64>, policy: &Option<String>) -> Multimap {
+    let mut params = Multimap::new();
+    params.insert(String::from("Action"), action.to_string());
+    params.insert(String::from("Version"), String::from("2011-06-15"));
+    if let Some(v) = duration_seconds {
+        params.insert(String::from("DurationSeconds"), v.to_string());
+    }
+    if let Some(v) = policy {
+        params.insert(String::from("Policy"), v.clone());
+    }
+    params
+}
+
+fn read_token_file(token_file: &Path) -> Result<String, Error> {
+    Ok(std::fs::read_to_string(token_file)?.trim().to_string())
+}
+
+pub struct AssumeRoleProvider {
+    sts_endpoint: BaseUrl,
+    access_key: String,
+    secret_key: String,
+    region: String,
+    role_arn: Option<String>,
+    role_session_name: Option<String>,
+    external_id: Option<String>,
+    duration_seconds: Option<u64>,
+    policy: Option<String>,
+    http_client: reqwest::Client,
+    cache: StsCache,
+}
+
+impl fmt::Debug for AssumeRoleProvider {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AssumeRoleProvider")
+            .field("sts_endpoint", &self.sts_endpoint)
+            .field("access_key", &self.access_key)
+            .field("secret_key", &REDACTED)
+            .field("region", &self.region)
+            .field("role_arn", &self.role_arn)
+            .field("role_session_name", &self.role_session_name)
+            .field("external_id", &self.external_id)
+            .field("duration_seconds", &self.duration_seconds)
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AssumeRoleProvider {
+    pub fn new(
+        sts_endpoint: &str,
+        access_key: &str,
+        secret_key: &str,
+    ) -> Result<AssumeRoleProvider, Error> {
+        Ok(AssumeRoleProvider {
+            sts_endpoint: BaseUrl::from_string(sts_endpoint.to_string())?,
+            access_key: access_key.to_string(),
+            secret_key: secret_key.to_string(),
+            region: String::new(),
+            role_arn: None,
+            role_session_name: None,
+            external_id: None,
+            duration_seconds: None,
+            policy: None,
+            http_client: reqwest::Client::new(),
+            cache: StsCache::default(),
+        })
+    }
+
+    pub fn region(mut self, region: &str) -> Self {
+        self.region = region.to_string();
+        self
+    }
+
+    pub fn role_arn(mut self, role_arn: &str) -> Self {
+        self.role_arn = Some(role_arn.to_string());
+        self
+    }
+
+    pub fn role_session_name(mut self, role_session_name: &str) -> Self {
+        self.role_session_name = Some(role_session_name.to_string());
+        self
+    }
+
+    pub fn external_id(mut self, external_id: &str) -> Self {
+        self.external_id = Some(external_id.to_string());
+        self
+    }
+
+    pub fn duration_seconds(mut self, duration_seconds: u64) -> Self {
+        self.duration_seconds = Some(duration_seconds);
+        self
+    }
+
+    pub fn policy(mut self, policy: &str) -> Self {
+        self.policy = Some(policy.to_string());
+        self
+    }
+
+    pub fn http_client(mut self, http_client: reqwest::Client) -> Self {
+        self.http_client = http_client;
+        self
+    }
+
+    async fn refresh(&self) -> Result<Credentials, Error> {
+        let mut params = sts_params("AssumeRole", self.duration_seconds, &self.policy);
+        if let Some(v) = &self.role_arn {
+            params.insert(String::from("RoleArn"), v.clone());
+        }
+        if let Some(v) = &self.role_session_name {
+            params.insert(String::from("RoleSessionName"), v.clone());
+        }
+        if let Some(v) = &self.external_id {
+            params.insert(String::from("ExternalId"), v.clone());
+        }
+
+        sts_request(
+            &self.http_client,
+            &self.sts_endpoint,
+            &self.region,
+            &params,
+            Some((&self.access_key, &self.secret_key)),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl Provider for AssumeRoleProvider {
+    async fn fetch(&self) -> Result<Credentials, Error> {
+        self.cache.fetch(self.refresh()).await
+    }
+}
+
+pub struct WebIdentityProvider {
+    sts_endpoint: BaseUrl,
+    token_file: PathBuf,
+    role_arn: Option<String>,
+    role_session_name: Option<String>,
+    duration_seconds: Option<u64>,
+    policy: Option<String>,
+    http_client: reqwest::Client,
+    cache: StsCache,
+}
+
+impl fmt::Debug for WebIdentityProvider {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WebIdentityProvider")
+            .field("sts_endpoint", &self.sts_endpoint)
+            .field("token_file", &self.token_file)
+            .field("role_arn", &self.role_arn)
+            .field("role_session_name", &self.role_session_name)
+            .field("duration_seconds", &self.duration_seconds)
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+impl WebIdentityProvider {
+    // Token file is read again on every refresh as it may be rotated, like
+    // projected Kubernetes service account tokens.
+    pub fn new(sts_endpoint: &str, token_file: &Path) -> Result<WebIdentityProvider, Error> {
+        Ok(WebIdentityProvider {
+            sts_endpoint: BaseUrl::from_string(sts_endpoint.to_string())?,
+            token_file: token_file.to_path_buf(),
+            role_arn: None,
+            role_session_name: None,
+            duration_seconds: None,
+            policy: None,
+            http_client: reqwest::Client::new(),
+            cache: StsCache::default(),
+        })
+    }
+
+    pub fn role_arn(mut self, role_arn: &str) -> Self {
+        self.role_arn = Some(role_arn.to_string());
+        self
+    }
+
+    pub fn role_session_name(mut self, role_session_name: &str) -> Self {
+        self.role_session_name = Some(role_session_name.to_string());
+        self
+    }
+
+    pub fn duration_seconds(mut self, duration_seconds: u64) -> Self {
+        self.duration_seconds = Some(duration_seconds);
+        self
+    }
+
+    pub fn policy(mut self, policy: &str) -> Self {
+        self.policy = Some(policy.to_string());
+        self
+    }
+
+    pub fn http_client(mut self, http_client: reqwest::Client) -> Self {
+        self.http_client = http_client;
+        self
+    }
+
+    async fn refresh(&self) -> Result<Credentials, Error> {
+        let mut params = sts_params(
+            "AssumeRoleWithWebIdentity",
+            self.duration_seconds,
+            &self.policy,
+        );
+        params.insert(
+            String::from("WebIdentityToken"),
+            read_token_file(&self.token_file)?,
+        );
+        if let Some(v) = &self.role_arn {
+            params.insert(String::from("RoleArn"), v.clone());
+            params.insert(
+                String::from("RoleSessionName"),
+                match &self.role_session_name {
+                    Some(v) => v.clone(),
+                    None => utc_now()
+                        .timestamp_nanos_opt()
+                        .unwrap_or_default()
+                        .to_string(),
+                },
+            );
+        }
+
+        sts_request(&self.http_client, &self.sts_endpoint, "", &params, None).await
+    }
+}
+
+#[async_trait]
+impl Provider for WebIdentityProvider {
+    async fn fetch(&self) -> Result<Credentials, Error> {
+        self.cache.fetch(self.refresh()).await
+    }
+}
+
+pub struct ClientGrantsProvider {
+    sts_endpoint: BaseUrl,
+    token_file: PathBuf,
+    duration_seconds: Option<u64>,
+    policy: Option<String>,
+    http_client: reqwest::Client,
+    cache: StsCache,
+}
+
+impl fmt::Debug for ClientGrantsProvider {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ClientGrantsProvider")
+            .field("sts_endpoint", &self.sts_endpoint)
+            .field("token_file", &self.token_file)
+            .field("duration_seconds", &self.duration_seconds)
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ClientGrantsProvider {
+    pub fn new(sts_endpoint: &str, token_file: &Path) -> Result<ClientGrantsProvider, Error> {
+        Ok(ClientGrantsProvider {
+            sts_endpoint: BaseUrl::from_string(sts_endpoint.to_string())?,
+            token_file: token_file.to_path_buf(),
+            duration_seconds: None,
+            policy: None,
+            http_client: reqwest::Client::new(),
+            cache: StsCache::default(),
+        })
+    }
+
+    pub fn duration_seconds(mut self, duration_seconds: u64) -> Self {
+        self.duration_seconds = Some(duration_seconds);
+        self
+    }
+
+    pub fn policy(mut self, policy: &str) -> Self {
+        self.policy = Some(policy.to_string());
+        self
+    }
+
+    pub fn http_client(mut self, http_client: reqwest::Client) -> Self {
+        self.http_client = http_client;
+        self
+    }
+
+    async fn refresh(&self) -> Result<Credentials, Error> {
+        let mut params = sts_params(
+            "AssumeRoleWithClientGrants",
+            self.duration_seconds,
+            &self.policy,
+        );
+        params.insert(String::from("Token"), read_token_file(&self.token_file)?);
+
+        sts_request(&self.http_client, &self.sts_endpoint, "", &params, None).await
+    }
+}
+
+#[async_trait]
+impl Provider for ClientGrantsProvider {
+    async fn fetch(&self) -> Result<Credentials, Error> {
+        self.cache.fetch(self.refresh()).await
+    }
+}
+
+pub struct LdapIdentityProvider {
+    sts_endpoint: BaseUrl,
+    ldap_username: String,
+    ldap_password: String,
+    duration_seconds: Option<u64>,
+    policy: Option<String>,
+    http_client: reqwest::Client,
+    cache: StsCache,
+}
+
+impl fmt::Debug for LdapIdentityProvider {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LdapIdentityProvider")
+            .field("sts_endpoint", &self.sts_endpoint)
+            .field("ldap_username", &self.ldap_username)
+            .field("ldap_password", &REDACTED)
+            .field("duration_seconds", &self.duration_seconds)
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+impl LdapIdentityProvider {
+    pub fn new(
+        sts_endpoint: &str,
+        ldap_username: &str,
+        ldap_password: &str,
+    ) -> Result<LdapIdentityProvider, Error> {
+        Ok(LdapIdentityProvider {
+            sts_endpoint: BaseUrl::from_string(sts_endpoint.to_string())?,
+            ldap_username: ldap_username.to_string(),
+            ldap_password: ldap_password.to_string(),
+            duration_seconds: None,
+            policy: None,
+            http_client: reqwest::Client::new(),
+            cache: StsCache::default(),
+        })
+    }
+
+    pub fn duration_seconds(mut self, duration_seconds: u64) -> Self {
+        self.duration_seconds = Some(duration_seconds);
+        self
+    }
+
+    pub fn policy(mut self, policy: &str) -> Self {
+        self.policy = Some(policy.to_string());
+        self
+    }
+
+    pub fn http_client(mut self, http_client: reqwest::Client) -> Self {
+        self.http_client = http_client;
+        self
+    }
+
+    async fn refresh(&self) -> Result<Credentials, Error> {
+        let mut params = sts_params(
+            "AssumeRoleWithLDAPIdentity",
+            self.duration_seconds,
+            &self.policy,
+        );
+        params.insert(String::from("LDAPUsername"), self.ldap_username.clone());
+        params.insert(String::from("LDAPPassword"), self.ldap_password.clone());
+
+        sts_request(&self.http_client, &self.sts_endpoint, "", &params, None).await
+    }
+}
+
+#[async_trait]
+impl Provider for LdapIdentityProvider {
+    async fn fetch(&self) -> Result<Credentials, Error> {
+        self.cache.fetch(self.refresh()).await
     }
 }
@@ -14,24 +14,206 @@
 // limitations under the License.
 
 extern crate alloc;
+use crate::s3::types::RetryPolicy;
 use crate::s3::utils::get_default_text;
 use bytes::{Buf, Bytes};
+use reqwest::header::HeaderMap;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use xmltree::Element;
 
+// Defines S3ErrorCode whose variants are named after the error codes they represent.
+macro_rules! s3_error_codes {
+    ($($code:ident,)+) => {
+        /// Error code of S3 or MinIO error response. Codes without a variant yet are kept as
+        /// [S3ErrorCode::Other]; new variants may be added in any release.
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        #[non_exhaustive]
+        pub enum S3ErrorCode {
+            $($code,)+
+            Other(String),
+        }
+
+        impl S3ErrorCode {
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(S3ErrorCode::$code => stringify!($code),)+
+                    S3ErrorCode::Other(v) => v,
+                }
+            }
+        }
+
+        impl From<&str> for S3ErrorCode {
+            fn from(value: &str) -> Self {
+                match value {
+                    $(stringify!($code) => S3ErrorCode::$code,)+
+                    _ => S3ErrorCode::Other(value.to_string()),
+                }
+            }
+        }
+    };
+}
+
+s3_error_codes! {
+    AccessControlListNotSupported,
+    AccessDenied,
+    AccessPointAlreadyOwnedByYou,
+    AccountProblem,
+    AllAccessDisabled,
+    AmbiguousGrantByEmailAddress,
+    AuthorizationHeaderMalformed,
+    BadDigest,
+    BadRequest,
+    BucketAlreadyExists,
+    BucketAlreadyOwnedByYou,
+    BucketNotEmpty,
+    ClientTokenConflict,
+    CredentialsNotSupported,
+    CrossLocationLoggingProhibited,
+    EntityTooLarge,
+    EntityTooSmall,
+    ExpiredToken,
+    ExpiredTokenException,
+    IllegalLocationConstraintException,
+    IllegalVersioningConfigurationException,
+    IncompleteBody,
+    IncorrectNumberOfFilesInPostRequest,
+    InlineDataTooLarge,
+    InternalError,
+    InvalidAccessKeyId,
+    InvalidAccessPoint,
+    InvalidArgument,
+    InvalidBucketName,
+    InvalidBucketState,
+    InvalidDigest,
+    InvalidEncryptionAlgorithmError,
+    InvalidLocationConstraint,
+    InvalidObjectState,
+    InvalidPart,
+    InvalidPartOrder,
+    InvalidPayer,
+    InvalidPolicyDocument,
+    InvalidRange,
+    InvalidRequest,
+    InvalidSecurity,
+    InvalidStorageClass,
+    InvalidTargetBucketForLogging,
+    InvalidToken,
+    InvalidURI,
+    KeyTooLongError,
+    MalformedACLError,
+    MalformedPOSTRequest,
+    MalformedXML,
+    MaxMessageLengthExceeded,
+    MaxPostPreDataLengthExceededError,
+    MetadataTooLarge,
+    MethodNotAllowed,
+    MissingContentLength,
+    MissingRequestBodyError,
+    MissingSecurityHeader,
+    NoSuchBucket,
+    NoSuchBucketPolicy,
+    NoSuchCORSConfiguration,
+    NoSuchKey,
+    NoSuchLifecycleConfiguration,
+    NoSuchObjectLockConfiguration,
+    NoSuchTagSet,
+    NoSuchUpload,
+    NoSuchVersion,
+    NoSuchWebsiteConfiguration,
+    NotImplemented,
+    NotModified,
+    ObjectLockConfigurationNotFoundError,
+    OperationAborted,
+    PermanentRedirect,
+    PreconditionFailed,
+    Redirect,
+    ReplicationConfigurationNotFoundError,
+    RequestError,
+    RequestIsNotMultiPartContent,
+    RequestLimitExceeded,
+    RequestThrottled,
+    RequestTimeTooSkewed,
+    RequestTimeout,
+    ResourceConflict,
+    ResourceNotFound,
+    RestoreAlreadyInProgress,
+    ServerSideEncryptionConfigurationNotFoundError,
+    ServiceUnavailable,
+    SignatureDoesNotMatch,
+    SlowDown,
+    TemporaryRedirect,
+    Throttling,
+    ThrottlingException,
+    TokenRefreshRequired,
+    TooManyBuckets,
+    UnexpectedContent,
+    UnresolvableGrantByEmailAddress,
+    UserKeyMustBeSpecified,
+    XAmzContentChecksumMismatch,
+    XAmzContentSHA256Mismatch,
+    XMinioAdminBucketQuotaExceeded,
+    XMinioAdminGroupNotEmpty,
+    XMinioAdminInvalidArgument,
+    XMinioAdminNoSuchGroup,
+    XMinioAdminNoSuchPolicy,
+    XMinioAdminNoSuchQuotaConfiguration,
+    XMinioAdminNoSuchUser,
+    XMinioAdminServiceAccountNotFound,
+    XMinioBackendDown,
+    XMinioInvalidIAMCredentials,
+    XMinioInvalidObjectName,
+    XMinioObjectTampered,
+    XMinioServerNotInitialized,
+    XMinioStorageFull,
+}
+
+impl Default for S3ErrorCode {
+    fn default() -> Self {
+        S3ErrorCode::Other(String::new())
+    }
+}
+
+impl From<String> for S3ErrorCode {
+    fn from(value: String) -> Self {
+        S3ErrorCode::from(value.as_str())
+    }
+}
+
+impl PartialEq<str> for S3ErrorCode {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for S3ErrorCode {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl fmt::Display for S3ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct ErrorResponse {
-    pub code: String,
+    pub code: S3ErrorCode,
     pub message: String,
     pub resource: String,
     pub request_id: String,
     pub host_id: String,
     pub bucket_name: String,
     pub object_name: String,
+    pub status_code: u16,
+    pub headers: HeaderMap,
 }
 
 impl ErrorResponse {
+    /// Parses XML error response body. Status code and headers are not part of the body and
+    /// are left empty.
     pub fn parse(body: &mut Bytes) -> Result<ErrorResponse, Error> {
         let root = match Element::parse(body.reader()) {
             Ok(v) => v,
@@ -39,16 +221,19 @@ impl ErrorResponse {
         };
 
         Ok(ErrorResponse {
-            code: get_default_text(&root, "Code"),
+            code: S3ErrorCode::from(get_default_text(&root, "Code")),
             message: get_default_text(&root, "Message"),
             resource: get_default_text(&root, "Resource"),
             request_id: get_default_text(&root, "RequestId"),
             host_id: get_default_text(&root, "HostId"),
             bucket_name: get_default_text(&root, "bucketName"),
             object_name: get_default_text(&root, "Key"),
+            ..Default::default()
         })
     }
 
+    /// Parses JSON error response body. Status code and headers are not part of the body and
+    /// are left empty.
     pub fn parse_json(body: &mut Bytes) -> Result<ErrorResponse, Error> {
         let json_error: JsonError = match serde_json::from_slice(body) {
             Ok(v) => v,
@@ -56,15 +241,23 @@ impl ErrorResponse {
         };
 
         Ok(ErrorResponse {
-            code: json_error.code,
+            code: S3ErrorCode::from(json_error.code),
             message: json_error.message,
             resource: json_error.resource,
             request_id: json_error.request_id,
             host_id: json_error.host_id,
             bucket_name: json_error.bucket_name,
             object_name: json_error.object_name,
+            ..Default::default()
         })
     }
+
+    // Sets status code and headers of the response this error was parsed from.
+    pub(crate) fn with_response(mut self, status_code: u16, headers: &HeaderMap) -> ErrorResponse {
+        self.status_code = status_code;
+        self.headers = headers.clone();
+        self
+    }
 }
 
 #[derive(Debug)]
@@ -98,12 +291,14 @@ pub enum Error {
     InvalidObjectSize(usize),
     MissingPartSize,
     InvalidPartCount(usize, usize, u16),
+    InvalidMaxBufferSize(usize, usize),
     SseTlsRequired(Option<String>),
     InsufficientData(usize, usize),
     InvalidLegalHold(String),
     InvalidSelectExpression(String),
     InvalidHeaderValueType(u8),
     CrcMismatch(String, u32, u32),
+    EtagMismatch(String, String),
     UnknownEventType(String),
     SelectError(String, String),
     UnsupportedApi(String),
@@ -123,10 +318,73 @@ pub enum Error {
     InvalidFilter,
     PostPolicyError(String),
     InvalidObjectLockConfig(String),
+    InvalidClientConfig(String),
+    CredentialsError(String),
+    InvalidPolicy(String),
+    AdminCryptoError(String),
+    InvalidAdminArgument(String),
+    InvalidChecksumAlgorithm(String),
+    ChecksumMismatch(String, String, String),
 }
 
 impl std::error::Error for Error {}
 
+impl Error {
+    /// Returns S3 error code if this is an error response from the server.
+    pub fn code(&self) -> Option<&S3ErrorCode> {
+        match self {
+            Error::S3Error(er) => Some(&er.code),
+            _ => None,
+        }
+    }
+
+    /// Returns HTTP status code if this error is caused by a server response.
+    pub fn status_code(&self) -> Option<u16> {
+        match self {
+            Error::S3Error(er) => Some(er.status_code),
+            Error::ServerError(sc) | Error::InvalidResponse(sc, _) => Some(*sc),
+            _ => None,
+        }
+    }
+
+    /// Returns whether requested bucket, object, version or upload does not exist.
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            Error::S3Error(er) => {
+                er.status_code == 404
+                    || matches!(
+                        er.code,
+                        S3ErrorCode::NoSuchBucket
+                            | S3ErrorCode::NoSuchKey
+                            | S3ErrorCode::NoSuchVersion
+                            | S3ErrorCode::NoSuchUpload
+                            | S3ErrorCode::ResourceNotFound
+                    )
+            }
+            _ => self.status_code() == Some(404),
+        }
+    }
+
+    /// Returns whether the request is denied by the server.
+    pub fn is_access_denied(&self) -> bool {
+        match self {
+            Error::S3Error(er) => {
+                matches!(
+                    er.code,
+                    S3ErrorCode::AccessDenied | S3ErrorCode::AllAccessDisabled
+                ) || er.status_code == 403
+            }
+            _ => self.status_code() == Some(403),
+        }
+    }
+
+    /// Returns whether the request may succeed if retried as per [RetryPolicy::default]. Client
+    /// configured with another policy retries as per [RetryPolicy::is_retryable] of that policy.
+    pub fn is_retryable(&self) -> bool {
+        RetryPolicy::default().is_retryable(self)
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -152,6 +410,7 @@ impl fmt::Display for Error {
 	    Error::InvalidObjectSize(s) => write!(f, "object size {} is not supported; maximum allowed 5TiB", s),
 	    Error::MissingPartSize => write!(f, "valid part size must be provided when object size is unknown"),
 	    Error::InvalidPartCount(os, ps, pc) => write!(f, "object size {} and part size {} make more than {} parts for upload", os, ps, pc),
+	    Error::InvalidMaxBufferSize(bs, ps) => write!(f, "max buffer size {} is smaller than part buffer size {}", bs, ps),
 	    Error::SseTlsRequired(m) => write!(f, "{}SSE operation must be performed over a secure connection", m.as_ref().map_or(String::new(), |v| v.clone())),
 	    Error::InsufficientData(ps, br) => write!(f, "not enough data in the stream; expected: {}, got: {} bytes", ps, br),
 	    Error::InvalidBaseUrl(m) => write!(f, "{}", m),
@@ -164,6 +423,7 @@ impl fmt::Display for Error {
 	    Error::InvalidSelectExpression(m) => write!(f, "{}", m),
 	    Error::InvalidHeaderValueType(v) => write!(f, "invalid header value type {}", v),
 	    Error::CrcMismatch(t, e, g) => write!(f, "{} CRC mismatch; expected: {}, got: {}", t, e, g),
+	    Error::EtagMismatch(e, g) => write!(f, "ETag mismatch; expected: {}, got: {}", e, g),
 	    Error::UnknownEventType(et) => write!(f, "unknown event type {}", et),
 	    Error::SelectError(ec, em) => write!(f, "error code: {}, error message: {}", ec, em),
 	    Error::UnsupportedApi(a) => write!(f, "{} API is not supported in Amazon AWS S3", a),
@@ -183,6 +443,13 @@ impl fmt::Display for Error {
 	    Error::InvalidFilter => write!(f, "only one of And, Prefix or Tag must be provided"),
 	    Error::PostPolicyError(m) => write!(f, "{}", m),
 	    Error::InvalidObjectLockConfig(m) => write!(f, "{}", m),
+	    Error::InvalidClientConfig(m) => write!(f, "{}", m),
+	    Error::CredentialsError(m) => write!(f, "unable to get credentials; {}", m),
+	    Error::InvalidPolicy(m) => write!(f, "invalid bucket policy; {}", m),
+	    Error::AdminCryptoError(m) => write!(f, "{}", m),
+	    Error::InvalidAdminArgument(m) => write!(f, "{}", m),
+	    Error::InvalidChecksumAlgorithm(m) => write!(f, "invalid checksum algorithm {}", m),
+	    Error::ChecksumMismatch(t, e, g) => write!(f, "{} checksum mismatch; expected: {}, got: {}", t, e, g),
        Error::JsonParseError(m) => write!(f, "{}", m),
 
 	}
@@ -14,7 +14,7 @@
 // limitations under the License.
 
 use crate::s3::error::Error;
-use crate::s3::utils::{to_query_string, Multimap};
+use crate::s3::utils::{to_query_string, urlencode_path, Multimap};
 use derivative::Derivative;
 use hyper::http::Method;
 use hyper::Uri;
@@ -174,17 +174,27 @@ impl BaseUrl {
             url.host = format!("{}.{}", bucket, url.host);
         }
 
-        if object_name.is_some() {
-            if !object_name.unwrap().starts_with('/') {
-                url.path.push('/');
-            }
-            // FIXME: urlencode path
-            url.path.push_str(object_name.unwrap());
+        if let Some(v) = object_name {
+            // Leading '/' is part of object name, hence separator is always added.
+            url.path.push('/');
+            url.path.push_str(&urlencode_path(v));
         }
 
         Ok(url)
     }
 
+    /// Returns path style URL of given path on the configured host; used by MinIO specific
+    /// APIs which do not address a bucket.
+    pub fn build_path_url(&self, path: &str, query: &Multimap) -> Url {
+        Url {
+            https: self.https,
+            host: self.host.clone(),
+            port: self.port,
+            path: path.to_string(),
+            query: query.clone(),
+        }
+    }
+
     pub fn from_string(s: String) -> Result<BaseUrl, Error> {
         let url = s.parse::<Uri>()?;
 
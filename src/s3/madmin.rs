@@ -0,0 +1,185 @@
+// MinIO Rust Library for Amazon S3 Compatible Cloud Storage
+// Copyright 2022 MinIO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Payload encryption of MinIO admin API compatible with madmin-go
+//!
+//! Encrypted data is laid out as `salt (32) | cipher id (1) | nonce (8) | stream`, where the key
+//! is derived from the secret key by argon2id and the stream is a sequence of sealed 16KiB
+//! fragments as written by sio-go.
+
+use crate::s3::error::Error;
+use aes_gcm::aead::generic_array::GenericArray;
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::Aes256Gcm;
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::ChaCha20Poly1305;
+use rand::RngCore;
+
+const SALT_LEN: usize = 32;
+const NONCE_LEN: usize = 8;
+const KEY_LEN: usize = 32;
+const TAG_LEN: usize = 16;
+const BUF_SIZE: usize = 16 * 1024;
+const HEADER_LEN: usize = SALT_LEN + 1 + NONCE_LEN;
+
+const FINAL_FRAGMENT: u8 = 0x80;
+
+/// AEAD cipher used to seal admin payloads
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cipher {
+    Aes256Gcm,
+    ChaCha20Poly1305,
+}
+
+impl Cipher {
+    fn id(&self) -> u8 {
+        match self {
+            Cipher::Aes256Gcm => 0x00,
+            Cipher::ChaCha20Poly1305 => 0x01,
+        }
+    }
+
+    fn from_id(id: u8) -> Result<Cipher, Error> {
+        match id {
+            0x00 => Ok(Cipher::Aes256Gcm),
+            0x01 => Ok(Cipher::ChaCha20Poly1305),
+            _ => Err(Error::AdminCryptoError(format!(
+                "unsupported encryption algorithm {}",
+                id
+            ))),
+        }
+    }
+}
+
+enum Sealer {
+    Aes256Gcm(Box<Aes256Gcm>),
+    ChaCha20Poly1305(ChaCha20Poly1305),
+}
+
+impl Sealer {
+    fn new(cipher: Cipher, key: &[u8]) -> Sealer {
+        let key = GenericArray::from_slice(key);
+        match cipher {
+            Cipher::Aes256Gcm => Sealer::Aes256Gcm(Box::new(Aes256Gcm::new(key))),
+            Cipher::ChaCha20Poly1305 => Sealer::ChaCha20Poly1305(ChaCha20Poly1305::new(key)),
+        }
+    }
+
+    fn seal(&self, nonce: &[u8], msg: &[u8], aad: &[u8]) -> Result<Vec<u8>, Error> {
+        let nonce = GenericArray::from_slice(nonce);
+        let payload = Payload { msg, aad };
+        match self {
+            Sealer::Aes256Gcm(c) => c.encrypt(nonce, payload),
+            Sealer::ChaCha20Poly1305(c) => c.encrypt(nonce, payload),
+        }
+        .map_err(|_| Error::AdminCryptoError(String::from("encryption failed")))
+    }
+
+    fn open(&self, nonce: &[u8], msg: &[u8], aad: &[u8]) -> Result<Vec<u8>, Error> {
+        let nonce = GenericArray::from_slice(nonce);
+        let payload = Payload { msg, aad };
+        match self {
+            Sealer::Aes256Gcm(c) => c.decrypt(nonce, payload),
+            Sealer::ChaCha20Poly1305(c) => c.decrypt(nonce, payload),
+        }
+        .map_err(|_| {
+            Error::AdminCryptoError(String::from(
+                "decryption failed; data is corrupted or secret key does not match",
+            ))
+        })
+    }
+}
+
+fn derive_key(password: &str, salt: &[u8]) -> Result<[u8; KEY_LEN], Error> {
+    let params = Params::new(64 * 1024, 1, 4, Some(KEY_LEN))
+        .map_err(|e| Error::AdminCryptoError(e.to_string()))?;
+    let mut key = [0_u8; KEY_LEN];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| Error::AdminCryptoError(e.to_string()))?;
+    Ok(key)
+}
+
+// Stream nonce is the random nonce followed by little endian fragment sequence number.
+fn fragment_nonce(nonce: &[u8], seq: u32) -> [u8; NONCE_LEN + 4] {
+    let mut v = [0_u8; NONCE_LEN + 4];
+    v[..NONCE_LEN].copy_from_slice(nonce);
+    v[NONCE_LEN..].copy_from_slice(&seq.to_le_bytes());
+    v
+}
+
+// Associated data of each fragment is a flag byte followed by the tag of sealing empty data
+// with sequence number zero.
+fn associated_data(sealer: &Sealer, nonce: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut ad = vec![0_u8];
+    ad.extend(sealer.seal(&fragment_nonce(nonce, 0), &[], &[])?);
+    Ok(ad)
+}
+
+/// Encrypts data with given secret key using AES-256-GCM.
+pub fn encrypt_data(password: &str, data: &[u8]) -> Result<Vec<u8>, Error> {
+    encrypt_data_with(password, data, Cipher::Aes256Gcm)
+}
+
+/// Encrypts data with given secret key and cipher.
+pub fn encrypt_data_with(password: &str, data: &[u8], cipher: Cipher) -> Result<Vec<u8>, Error> {
+    let mut header = [0_u8; HEADER_LEN];
+    rand::thread_rng().fill_bytes(&mut header[..SALT_LEN]);
+    header[SALT_LEN] = cipher.id();
+    rand::thread_rng().fill_bytes(&mut header[SALT_LEN + 1..]);
+    let (salt, nonce) = (&header[..SALT_LEN], &header[SALT_LEN + 1..]);
+
+    let sealer = Sealer::new(cipher, &derive_key(password, salt)?);
+    let mut ad = associated_data(&sealer, nonce)?;
+
+    let count = data.len().div_ceil(BUF_SIZE).max(1);
+    let mut encrypted = Vec::with_capacity(HEADER_LEN + data.len() + count * TAG_LEN);
+    encrypted.extend_from_slice(&header);
+    for i in 0..count {
+        let fragment = &data[i * BUF_SIZE..data.len().min((i + 1) * BUF_SIZE)];
+        if i + 1 == count {
+            ad[0] = FINAL_FRAGMENT;
+        }
+        encrypted.extend(sealer.seal(&fragment_nonce(nonce, i as u32 + 1), fragment, &ad)?);
+    }
+
+    Ok(encrypted)
+}
+
+/// Decrypts data encrypted by MinIO server or [encrypt_data] with given secret key.
+pub fn decrypt_data(password: &str, data: &[u8]) -> Result<Vec<u8>, Error> {
+    if data.len() < HEADER_LEN + TAG_LEN {
+        return Err(Error::AdminCryptoError(String::from(
+            "encrypted data is too short",
+        )));
+    }
+
+    let (salt, nonce) = (&data[..SALT_LEN], &data[SALT_LEN + 1..HEADER_LEN]);
+    let cipher = Cipher::from_id(data[SALT_LEN])?;
+    let sealer = Sealer::new(cipher, &derive_key(password, salt)?);
+    let mut ad = associated_data(&sealer, nonce)?;
+
+    let stream = &data[HEADER_LEN..];
+    let count = stream.len().div_ceil(BUF_SIZE + TAG_LEN);
+    let mut decrypted = Vec::with_capacity(stream.len());
+    for (i, fragment) in stream.chunks(BUF_SIZE + TAG_LEN).enumerate() {
+        if i + 1 == count {
+            ad[0] = FINAL_FRAGMENT;
+        }
+        decrypted.extend(sealer.open(&fragment_nonce(nonce, i as u32 + 1), fragment, &ad)?);
+    }
+
+    Ok(decrypted)
+}
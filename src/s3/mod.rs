@@ -13,13 +13,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod admin;
 pub mod args;
+pub mod checksum;
 pub mod client;
 pub mod creds;
 pub mod error;
 pub mod http;
+pub mod madmin;
+pub mod policy;
 pub mod response;
 pub mod signer;
 pub mod sse;
+pub mod transport;
 pub mod types;
 pub mod utils;
@@ -0,0 +1,732 @@
+// MinIO Rust Library for Amazon S3 Compatible Cloud Storage
+// Copyright 2022 MinIO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bucket policy documents
+
+use crate::s3::error::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt;
+
+pub const POLICY_VERSION: &str = "2012-10-17";
+
+const RESOURCE_ARN_PREFIX: &str = "arn:aws:s3:::";
+
+const COMMON_BUCKET_ACTIONS: &[&str] = &["s3:GetBucketLocation"];
+const READ_BUCKET_ACTIONS: &[&str] = &["s3:ListBucket"];
+const WRITE_BUCKET_ACTIONS: &[&str] = &["s3:ListBucketMultipartUploads"];
+const READ_OBJECT_ACTIONS: &[&str] = &["s3:GetObject"];
+const WRITE_OBJECT_ACTIONS: &[&str] = &[
+    "s3:AbortMultipartUpload",
+    "s3:DeleteObject",
+    "s3:ListMultipartUploadParts",
+    "s3:PutObject",
+];
+
+/// Condition block; maps condition operator to condition key and its values
+pub type Condition = BTreeMap<String, BTreeMap<String, Vec<String>>>;
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T> From<OneOrMany<T>> for Vec<T> {
+    fn from(value: OneOrMany<T>) -> Self {
+        match value {
+            OneOrMany::One(v) => vec![v],
+            OneOrMany::Many(v) => v,
+        }
+    }
+}
+
+// Condition values may be JSON strings, booleans or numbers.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Scalar {
+    Str(String),
+    Bool(bool),
+    Num(serde_json::Number),
+}
+
+impl From<Scalar> for String {
+    fn from(value: Scalar) -> Self {
+        match value {
+            Scalar::Str(v) => v,
+            Scalar::Bool(v) => v.to_string(),
+            Scalar::Num(v) => v.to_string(),
+        }
+    }
+}
+
+fn one_or_many<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Ok(OneOrMany::<T>::deserialize(deserializer)?.into())
+}
+
+fn condition<'de, D>(deserializer: D) -> Result<Option<Condition>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value =
+        Option::<BTreeMap<String, BTreeMap<String, OneOrMany<Scalar>>>>::deserialize(deserializer)?;
+    Ok(value.map(|c| {
+        c.into_iter()
+            .map(|(op, kv)| {
+                let kv = kv
+                    .into_iter()
+                    .map(|(k, v)| {
+                        let values: Vec<Scalar> = v.into();
+                        (k, values.into_iter().map(String::from).collect())
+                    })
+                    .collect();
+                (op, kv)
+            })
+            .collect()
+    }))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Effect {
+    Allow,
+    Deny,
+}
+
+/// Anonymous access level of objects under a prefix
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessLevel {
+    None,
+    ReadOnly,
+    WriteOnly,
+    ReadWrite,
+}
+
+impl AccessLevel {
+    fn new(read: bool, write: bool) -> AccessLevel {
+        match (read, write) {
+            (false, false) => AccessLevel::None,
+            (true, false) => AccessLevel::ReadOnly,
+            (false, true) => AccessLevel::WriteOnly,
+            (true, true) => AccessLevel::ReadWrite,
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<AccessLevel, Error> {
+        match s {
+            "none" => Ok(AccessLevel::None),
+            "readonly" => Ok(AccessLevel::ReadOnly),
+            "writeonly" => Ok(AccessLevel::WriteOnly),
+            "readwrite" => Ok(AccessLevel::ReadWrite),
+            _ => Err(Error::InvalidPolicy(format!("unknown access level {}", s))),
+        }
+    }
+
+    pub fn can_read(&self) -> bool {
+        matches!(self, AccessLevel::ReadOnly | AccessLevel::ReadWrite)
+    }
+
+    pub fn can_write(&self) -> bool {
+        matches!(self, AccessLevel::WriteOnly | AccessLevel::ReadWrite)
+    }
+}
+
+impl fmt::Display for AccessLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AccessLevel::None => write!(f, "none"),
+            AccessLevel::ReadOnly => write!(f, "readonly"),
+            AccessLevel::WriteOnly => write!(f, "writeonly"),
+            AccessLevel::ReadWrite => write!(f, "readwrite"),
+        }
+    }
+}
+
+/// Principal of a statement, either `"*"` or a map like `{"AWS": ["*"]}`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Principal {
+    Any,
+    Map(BTreeMap<String, Vec<String>>),
+}
+
+impl Serialize for Principal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Principal::Any => serializer.serialize_str("*"),
+            Principal::Map(m) => m.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Principal {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Str(String),
+            Map(BTreeMap<String, OneOrMany<String>>),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Str(v) if v == "*" => Ok(Principal::Any),
+            Raw::Str(v) => Err(serde::de::Error::custom(format!("invalid principal {}", v))),
+            Raw::Map(m) => Ok(Principal::Map(
+                m.into_iter().map(|(k, v)| (k, v.into())).collect(),
+            )),
+        }
+    }
+}
+
+impl Principal {
+    /// Returns principal matching every AWS identity, i.e. `{"AWS": ["*"]}`.
+    pub fn anyone() -> Principal {
+        Principal::Map(BTreeMap::from([(
+            String::from("AWS"),
+            vec![String::from("*")],
+        )]))
+    }
+
+    pub fn aws(identities: &[&str]) -> Principal {
+        Principal::Map(BTreeMap::from([(
+            String::from("AWS"),
+            identities.iter().map(|v| v.to_string()).collect(),
+        )]))
+    }
+
+    /// Returns whether this principal matches everyone.
+    pub fn is_anyone(&self) -> bool {
+        match self {
+            Principal::Any => true,
+            Principal::Map(m) => m.get("AWS").is_some_and(|v| v.iter().any(|v| v == "*")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Statement {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+    pub effect: Effect,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub principal: Option<Principal>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_principal: Option<Principal>,
+    #[serde(
+        default,
+        deserialize_with = "one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub action: Vec<String>,
+    #[serde(
+        default,
+        deserialize_with = "one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub not_action: Vec<String>,
+    #[serde(
+        default,
+        deserialize_with = "one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub resource: Vec<String>,
+    #[serde(
+        default,
+        deserialize_with = "one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub not_resource: Vec<String>,
+    #[serde(
+        default,
+        deserialize_with = "condition",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub condition: Option<Condition>,
+}
+
+impl Statement {
+    pub fn new(effect: Effect) -> Statement {
+        Statement {
+            sid: None,
+            effect,
+            principal: None,
+            not_principal: None,
+            action: Vec::new(),
+            not_action: Vec::new(),
+            resource: Vec::new(),
+            not_resource: Vec::new(),
+            condition: None,
+        }
+    }
+
+    pub fn allow() -> Statement {
+        Statement::new(Effect::Allow)
+    }
+
+    pub fn deny() -> Statement {
+        Statement::new(Effect::Deny)
+    }
+
+    pub fn sid(mut self, sid: &str) -> Self {
+        self.sid = Some(sid.to_string());
+        self
+    }
+
+    pub fn principal(mut self, principal: Principal) -> Self {
+        self.principal = Some(principal);
+        self
+    }
+
+    pub fn actions(mut self, actions: &[&str]) -> Self {
+        self.action.extend(actions.iter().map(|v| v.to_string()));
+        self
+    }
+
+    pub fn resources(mut self, resources: &[String]) -> Self {
+        self.resource.extend(resources.iter().cloned());
+        self
+    }
+
+    /// Adds condition values to given operator and key, e.g. `("StringEquals", "s3:prefix")`.
+    pub fn condition(mut self, operator: &str, key: &str, values: &[&str]) -> Self {
+        self.condition
+            .get_or_insert_with(Condition::new)
+            .entry(operator.to_string())
+            .or_default()
+            .entry(key.to_string())
+            .or_default()
+            .extend(values.iter().map(|v| v.to_string()));
+        self
+    }
+
+    // Returns whether this is a plain Allow statement for anyone, as written by canned policies.
+    fn is_anonymous_allow(&self) -> bool {
+        self.effect == Effect::Allow
+            && self.principal.as_ref().is_some_and(|p| p.is_anyone())
+            && self.not_principal.is_none()
+            && self.not_action.is_empty()
+            && self.not_resource.is_empty()
+    }
+
+    fn is_bucket_statement(&self, bucket_name: &str, actions: &[&str]) -> bool {
+        self.is_anonymous_allow()
+            && self.condition.is_none()
+            && self.resource == [bucket_arn(bucket_name)]
+            && self.action == actions
+    }
+
+    fn matches_action(&self, action: &str) -> bool {
+        self.action.iter().any(|v| wildcard_match(v, action))
+    }
+
+    fn validate(&self, index: usize, bucket_name: &str) -> Result<(), Error> {
+        let invalid = |msg: &str| {
+            Error::InvalidPolicy(format!(
+                "statement {}: {}",
+                self.sid.as_deref().unwrap_or(&index.to_string()),
+                msg
+            ))
+        };
+
+        if self.principal.is_some() == self.not_principal.is_some() {
+            return Err(invalid(
+                "exactly one of Principal or NotPrincipal must be provided",
+            ));
+        }
+        if self.action.is_empty() == self.not_action.is_empty() {
+            return Err(invalid(
+                "exactly one of Action or NotAction must be provided",
+            ));
+        }
+        if self.resource.is_empty() == self.not_resource.is_empty() {
+            return Err(invalid(
+                "exactly one of Resource or NotResource must be provided",
+            ));
+        }
+
+        for action in self.action.iter().chain(self.not_action.iter()) {
+            if action != "*" && !action.starts_with("s3:") {
+                return Err(invalid(&format!("unsupported action {}", action)));
+            }
+        }
+
+        for resource in self.resource.iter().chain(self.not_resource.iter()) {
+            let path = match resource.strip_prefix(RESOURCE_ARN_PREFIX) {
+                Some(v) => v,
+                None => return Err(invalid(&format!("invalid resource {}", resource))),
+            };
+            let bucket = path.split('/').next().unwrap_or_default();
+            if bucket != bucket_name && bucket != "*" {
+                return Err(invalid(&format!(
+                    "resource {} does not belong to bucket {}",
+                    resource, bucket_name
+                )));
+            }
+        }
+
+        if let Some(condition) = &self.condition {
+            for (operator, values) in condition.iter() {
+                if values.is_empty() || values.values().any(|v| v.is_empty()) {
+                    return Err(invalid(&format!("empty condition {}", operator)));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+/// IAM style policy document attached to a bucket
+pub struct BucketPolicy {
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub statement: Vec<Statement>,
+}
+
+impl Default for BucketPolicy {
+    fn default() -> Self {
+        BucketPolicy {
+            version: String::from(POLICY_VERSION),
+            id: None,
+            statement: Vec::new(),
+        }
+    }
+}
+
+// Matches `text` against `pattern` where `*` matches any sequence and `?` any single character.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((sp, st)) = star {
+            p = sp + 1;
+            t = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Returns ARN of given bucket.
+pub fn bucket_arn(bucket_name: &str) -> String {
+    format!("{}{}", RESOURCE_ARN_PREFIX, bucket_name)
+}
+
+/// Returns ARN matching all objects under given prefix of the bucket.
+pub fn objects_arn(bucket_name: &str, prefix: &str) -> String {
+    format!("{}{}/{}*", RESOURCE_ARN_PREFIX, bucket_name, prefix)
+}
+
+impl BucketPolicy {
+    pub fn new() -> BucketPolicy {
+        BucketPolicy::default()
+    }
+
+    pub fn parse(s: &str) -> Result<BucketPolicy, Error> {
+        Ok(serde_json::from_str(s)?)
+    }
+
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn statement(mut self, statement: Statement) -> Self {
+        self.statement.push(statement);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.statement.is_empty()
+    }
+
+    /// Returns policy allowing anyone to list and download objects under given prefix.
+    pub fn public_read(bucket_name: &str, prefix: &str) -> BucketPolicy {
+        let mut list = Statement::allow()
+            .principal(Principal::anyone())
+            .actions(&["s3:ListBucket"])
+            .resources(&[bucket_arn(bucket_name)]);
+        if !prefix.is_empty() {
+            list = list.condition("StringEquals", "s3:prefix", &[prefix]);
+        }
+
+        BucketPolicy::new()
+            .statement(
+                Statement::allow()
+                    .principal(Principal::anyone())
+                    .actions(&["s3:GetBucketLocation"])
+                    .resources(&[bucket_arn(bucket_name)]),
+            )
+            .statement(list)
+            .statement(
+                Statement::allow()
+                    .principal(Principal::anyone())
+                    .actions(&["s3:GetObject"])
+                    .resources(&[objects_arn(bucket_name, prefix)]),
+            )
+    }
+
+    /// Returns policy allowing anyone to upload, but not read, objects under given prefix.
+    pub fn upload_only(bucket_name: &str, prefix: &str) -> BucketPolicy {
+        BucketPolicy::new()
+            .statement(
+                Statement::allow()
+                    .principal(Principal::anyone())
+                    .actions(&["s3:GetBucketLocation", "s3:ListBucketMultipartUploads"])
+                    .resources(&[bucket_arn(bucket_name)]),
+            )
+            .statement(
+                Statement::allow()
+                    .principal(Principal::anyone())
+                    .actions(&[
+                        "s3:PutObject",
+                        "s3:AbortMultipartUpload",
+                        "s3:ListMultipartUploadParts",
+                    ])
+                    .resources(&[objects_arn(bucket_name, prefix)]),
+            )
+    }
+
+    /// Checks that the policy is well formed and only refers to given bucket.
+    pub fn validate(&self, bucket_name: &str) -> Result<(), Error> {
+        if self.version != POLICY_VERSION && self.version != "2008-10-17" {
+            return Err(Error::InvalidPolicy(format!(
+                "unsupported policy version {}",
+                self.version
+            )));
+        }
+
+        if self.statement.is_empty() {
+            return Err(Error::InvalidPolicy(String::from(
+                "policy must have at least one statement",
+            )));
+        }
+
+        for (i, statement) in self.statement.iter().enumerate() {
+            statement.validate(i, bucket_name)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns effective anonymous access to objects under given prefix. Only unconditional
+    /// statements are considered; statements using NotPrincipal or NotResource are ignored.
+    pub fn access(&self, bucket_name: &str, prefix: &str) -> AccessLevel {
+        // The ARN is matched literally, so a statement only counts if it covers whole prefix.
+        let arn = objects_arn(bucket_name, prefix);
+        let allowed = |action: &str| {
+            let mut allow = false;
+            for s in self.statement.iter() {
+                if s.condition.is_some()
+                    || s.not_principal.is_some()
+                    || !s.not_resource.is_empty()
+                    || !s.principal.as_ref().is_some_and(|p| p.is_anyone())
+                {
+                    continue;
+                }
+
+                let action_match = if s.not_action.is_empty() {
+                    s.matches_action(action)
+                } else {
+                    !s.not_action.iter().any(|v| wildcard_match(v, action))
+                };
+                if !action_match {
+                    continue;
+                }
+
+                match s.effect {
+                    Effect::Allow => {
+                        allow |= s.resource.iter().any(|r| wildcard_match(r, &arn));
+                    }
+                    Effect::Deny => {
+                        let inner = &arn[..arn.len() - 1];
+                        if s.resource
+                            .iter()
+                            .any(|r| wildcard_match(r, &arn) || r.starts_with(inner))
+                        {
+                            return false;
+                        }
+                    }
+                }
+            }
+            allow
+        };
+
+        AccessLevel::new(allowed("s3:GetObject"), allowed("s3:PutObject"))
+    }
+
+    /// Replaces canned anonymous statements of given prefix with ones granting given access
+    /// level. Statements not written by canned policies are left as they are.
+    pub fn set_access(&mut self, bucket_name: &str, prefix: &str, access: AccessLevel) {
+        let arn = objects_arn(bucket_name, prefix);
+
+        for s in self.statement.iter_mut() {
+            if !s.is_anonymous_allow() {
+                continue;
+            }
+
+            let object_actions = s.action.iter().all(|a| {
+                READ_OBJECT_ACTIONS.contains(&a.as_str())
+                    || WRITE_OBJECT_ACTIONS.contains(&a.as_str())
+            });
+            if s.condition.is_none() && object_actions {
+                s.resource.retain(|r| r != &arn);
+            }
+
+            if s.resource == [bucket_arn(bucket_name)] && s.action == READ_BUCKET_ACTIONS {
+                match &mut s.condition {
+                    None if prefix.is_empty() => s.resource.clear(),
+                    Some(c) if c.len() == 1 => {
+                        if let Some(kv) = c.get_mut("StringEquals") {
+                            if let Some(values) = kv.get_mut("s3:prefix") {
+                                values.retain(|v| v != prefix);
+                                if values.is_empty() {
+                                    s.resource.clear();
+                                }
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        self.statement.retain(|s| !s.resource.is_empty());
+
+        if access.can_read() {
+            self.add_list_bucket(bucket_name, prefix);
+            self.statement.push(
+                Statement::allow()
+                    .principal(Principal::anyone())
+                    .actions(READ_OBJECT_ACTIONS)
+                    .resources(std::slice::from_ref(&arn)),
+            );
+        }
+        if access.can_write() {
+            self.statement.push(
+                Statement::allow()
+                    .principal(Principal::anyone())
+                    .actions(WRITE_OBJECT_ACTIONS)
+                    .resources(&[arn]),
+            );
+        }
+
+        // Bucket level statements are shared by all prefixes, so keep them while any canned
+        // object statement remains.
+        let uses = |actions: &[&str]| {
+            self.statement.iter().any(|s| {
+                s.is_anonymous_allow()
+                    && s.condition.is_none()
+                    && s.action.iter().any(|a| actions.contains(&a.as_str()))
+            })
+        };
+        let read = uses(READ_OBJECT_ACTIONS);
+        let write = uses(WRITE_OBJECT_ACTIONS);
+        self.ensure_bucket_statement(bucket_name, WRITE_BUCKET_ACTIONS, write);
+        self.ensure_bucket_statement(bucket_name, COMMON_BUCKET_ACTIONS, read || write);
+    }
+
+    /// Returns this policy with canned anonymous statements of given prefix set to given
+    /// access level.
+    pub fn with_access(mut self, bucket_name: &str, prefix: &str, access: AccessLevel) -> Self {
+        self.set_access(bucket_name, prefix, access);
+        self
+    }
+
+    fn add_list_bucket(&mut self, bucket_name: &str, prefix: &str) {
+        if prefix.is_empty() {
+            if !self
+                .statement
+                .iter()
+                .any(|s| s.is_bucket_statement(bucket_name, READ_BUCKET_ACTIONS))
+            {
+                self.statement.push(
+                    Statement::allow()
+                        .principal(Principal::anyone())
+                        .actions(READ_BUCKET_ACTIONS)
+                        .resources(&[bucket_arn(bucket_name)]),
+                );
+            }
+            return;
+        }
+
+        for s in self.statement.iter_mut() {
+            if !s.is_anonymous_allow()
+                || s.resource != [bucket_arn(bucket_name)]
+                || s.action != READ_BUCKET_ACTIONS
+            {
+                continue;
+            }
+            if let Some(c) = &mut s.condition {
+                if c.len() == 1 {
+                    if let Some(values) = c
+                        .get_mut("StringEquals")
+                        .and_then(|kv| kv.get_mut("s3:prefix"))
+                    {
+                        if !values.iter().any(|v| v == prefix) {
+                            values.push(prefix.to_string());
+                        }
+                        return;
+                    }
+                }
+            }
+        }
+
+        self.statement.push(
+            Statement::allow()
+                .principal(Principal::anyone())
+                .actions(READ_BUCKET_ACTIONS)
+                .resources(&[bucket_arn(bucket_name)])
+                .condition("StringEquals", "s3:prefix", &[prefix]),
+        );
+    }
+
+    fn ensure_bucket_statement(&mut self, bucket_name: &str, actions: &[&str], present: bool) {
+        let exists = self
+            .statement
+            .iter()
+            .any(|s| s.is_bucket_statement(bucket_name, actions));
+        if present && !exists {
+            self.statement.insert(
+                0,
+                Statement::allow()
+                    .principal(Principal::anyone())
+                    .actions(actions)
+                    .resources(&[bucket_arn(bucket_name)]),
+            );
+        } else if !present {
+            self.statement
+                .retain(|s| !s.is_bucket_statement(bucket_name, actions));
+        }
+    }
+}
@@ -13,10 +13,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::s3::checksum::Checksum;
 use crate::s3::error::Error;
+use crate::s3::policy::{AccessLevel, BucketPolicy};
+use crate::s3::transport::HttpResponse;
 use crate::s3::types::{
-    parse_legal_hold, Bucket, Item, LifecycleConfig, NotificationConfig, ObjectLockConfig, Quota,
-    ReplicationConfig, RetentionMode, SelectProgress, SseConfig,
+    parse_legal_hold, Bucket, Item, LifecycleConfig, MultipartUpload, NotificationConfig,
+    ObjectLockConfig, PartInfo, Quota, ReplicationConfig, RetentionMode, SelectProgress, SseConfig,
 };
 use crate::s3::utils::{
     copy_slice, crc32, from_http_header_value, from_iso8601utc, get_text, uint32, UtcTime,
@@ -68,6 +71,39 @@ pub type AbortMultipartUploadResponse = UploadIdResponse;
 
 pub type CreateMultipartUploadResponse = UploadIdResponse;
 
+#[derive(Clone, Debug)]
+pub struct ListPartsResponse {
+    pub headers: HeaderMap,
+    pub region: String,
+    pub bucket_name: String,
+    pub object_name: String,
+    pub upload_id: String,
+    pub storage_class: Option<String>,
+    pub part_number_marker: Option<u16>,
+    pub next_part_number_marker: Option<u16>,
+    pub max_parts: Option<u16>,
+    pub is_truncated: bool,
+    pub parts: Vec<PartInfo>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ListMultipartUploadsResponse {
+    pub headers: HeaderMap,
+    pub region: String,
+    pub bucket_name: String,
+    pub encoding_type: Option<String>,
+    pub prefix: Option<String>,
+    pub delimiter: Option<String>,
+    pub key_marker: Option<String>,
+    pub upload_id_marker: Option<String>,
+    pub next_key_marker: Option<String>,
+    pub next_upload_id_marker: Option<String>,
+    pub max_uploads: Option<u16>,
+    pub is_truncated: bool,
+    pub uploads: Vec<MultipartUpload>,
+    pub common_prefixes: Vec<String>,
+}
+
 #[derive(Debug)]
 pub struct PutObjectBaseResponse {
     pub headers: HeaderMap,
@@ -76,6 +112,7 @@ pub struct PutObjectBaseResponse {
     pub location: String,
     pub etag: String,
     pub version_id: Option<String>,
+    pub checksum: Option<Checksum>,
 }
 
 pub type CompleteMultipartUploadResponse = PutObjectBaseResponse;
@@ -109,6 +146,7 @@ pub struct StatObjectResponse {
     pub legal_hold: Option<bool>,
     pub delete_marker: Option<bool>,
     pub user_metadata: HashMap<String, String>,
+    pub checksum: Option<Checksum>,
 }
 
 impl StatObjectResponse {
@@ -179,6 +217,7 @@ impl StatObjectResponse {
             legal_hold,
             delete_marker,
             user_metadata,
+            checksum: Checksum::from_headers(headers),
         })
     }
 }
@@ -291,7 +330,7 @@ pub struct SelectObjectContentResponse {
     pub object_name: String,
     pub progress: SelectProgress,
 
-    resp: reqwest::Response,
+    resp: HttpResponse,
 
     done: bool,
     buf: VecDeque<u8>,
@@ -316,7 +355,7 @@ pub struct SelectObjectContentResponse {
 
 impl SelectObjectContentResponse {
     pub fn new(
-        resp: reqwest::Response,
+        resp: HttpResponse,
         region: &str,
         bucket_name: &str,
         object_name: &str,
@@ -684,11 +723,22 @@ pub struct GetBucketPolicyResponse {
     pub headers: HeaderMap,
     pub region: String,
     pub bucket_name: String,
-    pub config: String,
+    pub config: BucketPolicy,
 }
 
 pub type SetBucketPolicyResponse = BucketResponse;
 
+#[derive(Clone, Debug)]
+pub struct GetAnonymousAccessResponse {
+    pub headers: HeaderMap,
+    pub region: String,
+    pub bucket_name: String,
+    pub prefix: String,
+    pub access: AccessLevel,
+}
+
+pub type SetAnonymousAccessResponse = BucketResponse;
+
 pub type DeleteBucketReplicationResponse = BucketResponse;
 
 #[derive(Clone, Debug)]
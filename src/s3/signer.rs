@@ -17,10 +17,23 @@ use crate::s3::utils::{
     get_canonical_headers, get_canonical_query_string, sha256_hash, to_amz_date, to_signer_date,
     Multimap, UtcTime,
 };
+use bytes::{BufMut, Bytes, BytesMut};
 use hex::encode as hexencode;
 use hmac::{Hmac, Mac};
 use hyper::http::Method;
 use sha2::Sha256;
+use std::fmt;
+
+/// Content SHA256 value of requests whose body is sent in signed aws-chunked encoding
+pub const STREAMING_PAYLOAD: &str = "STREAMING-AWS4-HMAC-SHA256-PAYLOAD";
+
+/// Content SHA256 value of requests whose body is not signed
+pub const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+/// Size of data in each signed chunk of aws-chunked encoding
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+const EMPTY_SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
 
 pub fn hmac_hash(key: &[u8], data: &[u8]) -> Vec<u8> {
     let mut hasher = Hmac::<Sha256>::new_from_slice(key).expect("HMAC can take key of any size");
@@ -114,7 +127,7 @@ pub fn sign_v4(
     secret_key: &str,
     content_sha256: &str,
     date: UtcTime,
-) {
+) -> String {
     let scope = get_scope(date, region, service_name);
     let (signed_headers, canonical_headers) = get_canonical_headers(headers);
     let canonical_query_string = get_canonical_query_string(query_params);
@@ -132,6 +145,7 @@ pub fn sign_v4(
     let authorization = get_authorization(access_key, &scope, &signed_headers, &signature);
 
     headers.insert("Authorization".to_string(), authorization);
+    signature
 }
 
 pub fn sign_v4_s3(
@@ -144,7 +158,7 @@ pub fn sign_v4_s3(
     secret_key: &str,
     content_sha256: &str,
     date: UtcTime,
-) {
+) -> String {
     sign_v4(
         "s3",
         method,
@@ -169,7 +183,7 @@ pub fn sign_v4_sts(
     secret_key: &str,
     content_sha256: &str,
     date: UtcTime,
-) {
+) -> String {
     sign_v4(
         "sts",
         method,
@@ -221,7 +235,7 @@ pub fn presign_v4(
         &canonical_query_string,
         &canonical_headers,
         signed_headers,
-        "UNSIGNED-PAYLOAD",
+        UNSIGNED_PAYLOAD,
     );
     let string_to_sign = get_string_to_sign(date, &scope, &canonical_request_hash);
     let signing_key = get_signing_key(secret_key, date, region, "s3");
@@ -239,3 +253,70 @@ pub fn post_presign_v4(
     let signing_key = get_signing_key(secret_key, date, region, "s3");
     return get_signature(signing_key.as_slice(), string_to_sign.as_bytes());
 }
+
+/// Returns length of data of given length in aws-chunked encoding.
+pub fn chunked_content_length(length: usize) -> usize {
+    // Each chunk is "<hex size>;chunk-signature=<64 hex>\r\n<data>\r\n" and the body ends
+    // with an empty chunk.
+    let frame_length = |size: usize| format!("{:x}", size).len() + 17 + 64 + 2 + size + 2;
+    let partial_chunk = match length % CHUNK_SIZE {
+        0 => 0,
+        v => frame_length(v),
+    };
+    (length / CHUNK_SIZE) * frame_length(CHUNK_SIZE) + partial_chunk + frame_length(0)
+}
+
+/// Signs chunks of aws-chunked encoded body; each chunk signature is chained to the previous
+/// one starting from the seed signature of the request.
+#[derive(Clone)]
+pub struct ChunkSigner {
+    signing_key: Vec<u8>,
+    amz_date: String,
+    scope: String,
+    prev_signature: String,
+}
+
+impl fmt::Debug for ChunkSigner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ChunkSigner")
+            .field("amz_date", &self.amz_date)
+            .field("scope", &self.scope)
+            .field("prev_signature", &self.prev_signature)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ChunkSigner {
+    pub fn new(secret_key: &str, date: UtcTime, region: &str, seed_signature: String) -> Self {
+        ChunkSigner {
+            signing_key: get_signing_key(secret_key, date, region, "s3"),
+            amz_date: to_amz_date(date),
+            scope: get_scope(date, region, "s3"),
+            prev_signature: seed_signature,
+        }
+    }
+
+    /// Returns signature of given chunk.
+    pub fn sign(&mut self, chunk: &[u8]) -> String {
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256-PAYLOAD\n{}\n{}\n{}\n{}\n{}",
+            self.amz_date,
+            self.scope,
+            self.prev_signature,
+            EMPTY_SHA256,
+            sha256_hash(chunk)
+        );
+        self.prev_signature = get_signature(&self.signing_key, string_to_sign.as_bytes());
+        self.prev_signature.clone()
+    }
+
+    /// Returns given chunk framed with its signature. Empty chunk terminates the body.
+    pub fn frame(&mut self, chunk: &[u8]) -> Bytes {
+        let header = format!("{:x};chunk-signature={}\r\n", chunk.len(), self.sign(chunk));
+        let mut buf = BytesMut::with_capacity(header.len() + chunk.len() + 2);
+        buf.put_slice(header.as_bytes());
+        buf.put_slice(chunk);
+        buf.put_slice(b"\r\n");
+        buf.freeze()
+    }
+}
@@ -0,0 +1,350 @@
+// MinIO Rust Library for Amazon S3 Compatible Cloud Storage
+// Copyright 2022 MinIO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! HTTP transport used by [Client](crate::s3::client::Client)
+
+use crate::s3::error::Error;
+use crate::s3::http::Url;
+use crate::s3::signer::{ChunkSigner, CHUNK_SIZE};
+use crate::s3::utils::{to_query_string, Multimap};
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use futures_core::Stream;
+use futures_util::{StreamExt, TryStreamExt};
+use hyper::http::{HeaderMap, Method, StatusCode};
+use std::fmt;
+use std::pin::Pin;
+use std::sync::Mutex;
+
+/// Signed request ready to be sent
+#[derive(Clone)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: Url,
+    pub headers: Multimap,
+    pub body: Bytes,
+    pub chunk_signer: Option<ChunkSigner>, // body is sent in aws-chunked encoding if present.
+}
+
+/// Returns value of given header with credentials redacted, safe to be logged. Signature of
+/// `Authorization` is redacted, keeping access key and signed headers to help debugging.
+pub fn redact_header(name: &str, value: &str) -> String {
+    const REDACTED: &str = "**REDACTED**";
+    match name.to_lowercase().as_str() {
+        "authorization" => match value.find("Signature=") {
+            Some(i) => format!("{}Signature={}", &value[..i], REDACTED),
+            None => String::from(REDACTED),
+        },
+        "x-amz-security-token"
+        | "x-amz-server-side-encryption-customer-key"
+        | "x-amz-copy-source-server-side-encryption-customer-key" => String::from(REDACTED),
+        _ => value.to_string(),
+    }
+}
+
+// Returns sorted header lines with credentials redacted.
+fn trace_headers<'a>(headers: impl Iterator<Item = (&'a str, String)>) -> String {
+    let mut lines: Vec<String> = headers
+        .map(|(k, v)| format!("{}: {}", k, redact_header(k, &v)))
+        .collect();
+    lines.sort();
+    lines.join("\n")
+}
+
+impl HttpRequest {
+    // Chunks of the body as sent on the wire; chunks are signed lazily from buffered body.
+    fn frames(&self) -> impl Iterator<Item = Bytes> + Send + 'static {
+        let mut body = self.body.clone();
+        let mut signer = self.chunk_signer.clone();
+        let mut done = false;
+        std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+            let chunk = body.split_to(body.len().min(CHUNK_SIZE));
+            match signer.as_mut() {
+                Some(s) => {
+                    done = chunk.is_empty();
+                    Some(s.frame(&chunk))
+                }
+                None => {
+                    done = true;
+                    Some(chunk)
+                }
+            }
+        })
+    }
+
+    /// Returns body as sent on the wire.
+    pub fn encoded_body(&self) -> Bytes {
+        if self.chunk_signer.is_none() {
+            return self.body.clone();
+        }
+        let mut buf = BytesMut::new();
+        self.frames().for_each(|v| buf.extend_from_slice(&v));
+        buf.freeze()
+    }
+
+    /// Returns body as sent on the wire as a stream.
+    pub fn body_stream(&self) -> impl Stream<Item = Result<Bytes, Error>> + Send + 'static {
+        futures_util::stream::iter(self.frames().map(Ok))
+    }
+
+    /// Returns request line and headers with credentials redacted, like `mc --debug`.
+    pub fn trace(&self) -> String {
+        let mut target = self.url.path.clone();
+        if !self.url.query.is_empty() {
+            target.push('?');
+            target.push_str(&to_query_string(&self.url.query));
+        }
+        let headers = self
+            .headers
+            .iter_all()
+            .flat_map(|(k, values)| values.iter().map(move |v| (k.as_str(), v.clone())));
+        format!(
+            "{} {} HTTP/1.1\n{}",
+            self.method,
+            target,
+            trace_headers(headers)
+        )
+    }
+}
+
+// Credentials in headers are redacted as requests are printed by MemoryTransport and callers.
+impl fmt::Debug for HttpRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let headers: Vec<(&str, String)> = self
+            .headers
+            .iter_all()
+            .flat_map(|(k, values)| {
+                values
+                    .iter()
+                    .map(move |v| (k.as_str(), redact_header(k, v)))
+            })
+            .collect();
+        f.debug_struct("HttpRequest")
+            .field("method", &self.method)
+            .field("url", &self.url)
+            .field("headers", &headers)
+            .field("body_len", &self.body.len())
+            .field("chunk_signer", &self.chunk_signer)
+            .finish()
+    }
+}
+
+/// Response body, either fully buffered or streamed from the server
+pub enum Body {
+    Full(Option<Bytes>),
+    Stream(Pin<Box<dyn Stream<Item = Result<Bytes, Error>> + Send>>),
+}
+
+impl Body {
+    pub fn empty() -> Body {
+        Body::Full(None)
+    }
+
+    /// Returns next chunk of the body or None at the end.
+    pub async fn chunk(&mut self) -> Result<Option<Bytes>, Error> {
+        match self {
+            Body::Full(v) => Ok(v.take().filter(|b| !b.is_empty())),
+            Body::Stream(s) => s.next().await.transpose(),
+        }
+    }
+
+    /// Reads remaining body into memory.
+    pub async fn bytes(mut self) -> Result<Bytes, Error> {
+        if let Body::Full(v) = &mut self {
+            return Ok(v.take().unwrap_or_default());
+        }
+
+        let mut buf = BytesMut::new();
+        while let Some(v) = self.chunk().await? {
+            buf.extend_from_slice(&v);
+        }
+        Ok(buf.freeze())
+    }
+}
+
+impl fmt::Debug for Body {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Body::Full(v) => f.debug_tuple("Full").field(v).finish(),
+            Body::Stream(_) => f.write_str("Stream"),
+        }
+    }
+}
+
+impl From<Bytes> for Body {
+    fn from(value: Bytes) -> Self {
+        Body::Full(Some(value))
+    }
+}
+
+impl From<Vec<u8>> for Body {
+    fn from(value: Vec<u8>) -> Self {
+        Body::Full(Some(Bytes::from(value)))
+    }
+}
+
+impl From<&'static str> for Body {
+    fn from(value: &'static str) -> Self {
+        Body::Full(Some(Bytes::from_static(value.as_bytes())))
+    }
+}
+
+/// Response returned by a [Transport]
+#[derive(Debug)]
+pub struct HttpResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Body,
+}
+
+impl HttpResponse {
+    pub fn new(status: StatusCode, headers: HeaderMap, body: Body) -> HttpResponse {
+        HttpResponse {
+            status,
+            headers,
+            body,
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    pub fn into_body(self) -> Body {
+        self.body
+    }
+
+    pub async fn chunk(&mut self) -> Result<Option<Bytes>, Error> {
+        self.body.chunk().await
+    }
+
+    /// Returns status line and headers with credentials redacted, like `mc --debug`.
+    pub fn trace(&self) -> String {
+        let headers = self.headers.iter().map(|(k, v)| {
+            (
+                k.as_str(),
+                String::from_utf8_lossy(v.as_bytes()).to_string(),
+            )
+        });
+        format!("HTTP/1.1 {}\n{}", self.status, trace_headers(headers))
+    }
+
+    pub async fn bytes(self) -> Result<Bytes, Error> {
+        self.body.bytes().await
+    }
+
+    pub async fn text(self) -> Result<String, Error> {
+        Ok(String::from_utf8(self.bytes().await?.to_vec())?)
+    }
+}
+
+/// Sends signed requests on behalf of [Client](crate::s3::client::Client)
+#[async_trait]
+pub trait Transport: fmt::Debug + Send + Sync {
+    async fn send(&self, req: HttpRequest) -> Result<HttpResponse, Error>;
+}
+
+/// Default transport backed by [reqwest::Client]
+#[derive(Clone, Debug, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    pub fn new(client: reqwest::Client) -> ReqwestTransport {
+        ReqwestTransport { client }
+    }
+}
+
+#[async_trait]
+impl Transport for ReqwestTransport {
+    async fn send(&self, req: HttpRequest) -> Result<HttpResponse, Error> {
+        let mut builder = self.client.request(req.method.clone(), req.url.to_string());
+
+        for (key, values) in req.headers.iter_all() {
+            for value in values {
+                builder = builder.header(key, value);
+            }
+        }
+
+        if req.method == Method::PUT || req.method == Method::POST {
+            builder = match req.chunk_signer {
+                Some(_) => builder.body(reqwest::Body::wrap_stream(req.body_stream())),
+                None => builder.body(req.body),
+            };
+        }
+
+        let resp = builder.send().await?;
+        let status = resp.status();
+        let headers = resp.headers().clone();
+        let stream = resp.bytes_stream().map_err(Error::from);
+
+        Ok(HttpResponse::new(
+            status,
+            headers,
+            Body::Stream(Box::pin(stream)),
+        ))
+    }
+}
+
+type Handler = dyn Fn(&HttpRequest) -> HttpResponse + Send + Sync;
+
+/// In-memory transport answering requests with a caller supplied handler; useful to test
+/// against scripted S3 responses without a server.
+pub struct MemoryTransport {
+    handler: Box<Handler>,
+    requests: Mutex<Vec<HttpRequest>>,
+}
+
+impl MemoryTransport {
+    pub fn new<F>(handler: F) -> MemoryTransport
+    where
+        F: Fn(&HttpRequest) -> HttpResponse + Send + Sync + 'static,
+    {
+        MemoryTransport {
+            handler: Box::new(handler),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns requests received so far.
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl fmt::Debug for MemoryTransport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MemoryTransport")
+            .field("requests", &self.requests)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl Transport for MemoryTransport {
+    async fn send(&self, req: HttpRequest) -> Result<HttpResponse, Error> {
+        let resp = (self.handler)(&req);
+        self.requests.lock().unwrap().push(req);
+        Ok(resp)
+    }
+}
@@ -13,14 +13,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::s3::error::Error;
+use crate::s3::error::{Error, S3ErrorCode};
 use crate::s3::utils::{
     from_iso8601utc, get_default_text, get_option_text, get_text, to_iso8601utc, UtcTime,
 };
 pub use byte_unit::Byte;
+use hyper::http::Method;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
+use std::time::Duration;
 use xmltree::Element;
 
 #[derive(Clone, Debug, Default)]
@@ -50,6 +53,27 @@ pub struct Bucket {
 pub struct Part {
     pub number: u16,
     pub etag: String,
+    pub checksum: Option<String>, // base64 encoded checksum of the algorithm used for the upload
+}
+
+#[derive(Clone, Debug)]
+pub struct PartInfo {
+    pub number: u16,
+    pub etag: String,
+    pub size: usize,
+    pub last_modified: Option<UtcTime>,
+}
+
+#[derive(Clone, Debug)]
+pub struct MultipartUpload {
+    pub object_name: String,
+    pub upload_id: String,
+    pub initiated: Option<UtcTime>,
+    pub storage_class: Option<String>,
+    pub owner_id: Option<String>,
+    pub owner_name: Option<String>,
+    pub initiator_id: Option<String>,
+    pub initiator_name: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -565,6 +589,16 @@ pub struct NotificationRecord {
     pub source: Option<Source>,
 }
 
+impl NotificationRecord {
+    pub fn bucket(&self) -> Option<&S3Bucket> {
+        self.s3.as_ref().and_then(|v| v.bucket.as_ref())
+    }
+
+    pub fn object(&self) -> Option<&S3Object> {
+        self.s3.as_ref().and_then(|v| v.object.as_ref())
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct NotificationRecords {
     #[serde(alias = "Records")]
@@ -1890,3 +1924,113 @@ pub struct Quota {
     pub quota: byte_unit::Byte,
     pub quotatype: Option<QuotaType>,
 }
+
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: f64, // fraction of delay randomly subtracted; 0.0 disables jitter.
+    pub retryable_codes: Vec<S3ErrorCode>,
+    pub retryable_status_codes: Vec<u16>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(1),
+            jitter: 1.0,
+            retryable_codes: vec![
+                S3ErrorCode::RequestError,
+                S3ErrorCode::RequestTimeout,
+                S3ErrorCode::Throttling,
+                S3ErrorCode::ThrottlingException,
+                S3ErrorCode::RequestLimitExceeded,
+                S3ErrorCode::RequestThrottled,
+                S3ErrorCode::InternalError,
+                S3ErrorCode::ExpiredToken,
+                S3ErrorCode::ExpiredTokenException,
+                S3ErrorCode::SlowDown,
+            ],
+            retryable_status_codes: vec![408, 429, 499, 500, 502, 503, 504, 520],
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn none() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.max_attempts < 1 {
+            return Err(Error::InvalidClientConfig(String::from(
+                "retry max attempts must be at least 1",
+            )));
+        }
+
+        if !(0.0..=1.0).contains(&self.jitter) {
+            return Err(Error::InvalidClientConfig(String::from(
+                "retry jitter must be between 0.0 and 1.0",
+            )));
+        }
+
+        if self.base_delay > self.max_delay {
+            return Err(Error::InvalidClientConfig(String::from(
+                "retry base delay must not exceed max delay",
+            )));
+        }
+
+        Ok(())
+    }
+
+    // Only requests which do not change state on repeat are replayed; POST (e.g.
+    // CompleteMultipartUpload, DeleteObjects) is never retried.
+    pub fn is_replayable(&self, method: &Method) -> bool {
+        matches!(
+            *method,
+            Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS
+        )
+    }
+
+    pub fn is_retryable(&self, err: &Error) -> bool {
+        match err {
+            // Other errors, like invalid request, fail the same way when retried.
+            Error::HttpError(e) => e.is_connect() || e.is_timeout(),
+            Error::S3Error(er) => self.retryable_codes.contains(&er.code),
+            Error::ServerError(sc) => self.retryable_status_codes.contains(sc),
+            Error::InvalidResponse(sc, _) => self.retryable_status_codes.contains(sc),
+            _ => false,
+        }
+    }
+
+    // Exponential backoff capped at max_delay, reduced by random jitter.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2_u32.saturating_pow(attempt.saturating_sub(1).min(31));
+        let mut delay = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        if self.jitter > 0.0 {
+            let jitter: f64 = rand::thread_rng().gen_range(0.0..=self.jitter);
+            delay = delay.mul_f64(1.0 - jitter);
+        }
+        delay
+    }
+}
+
+/// How payload of object uploads is signed. Each part is buffered in memory either way, so
+/// signing mode does not change memory used by uploads.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PayloadSigning {
+    /// SHA256 of whole payload is signed; payload is hashed before it is sent.
+    #[default]
+    Full,
+    /// Payload is sent in aws-chunked encoding with a signature per chunk, signed while it is
+    /// sent instead of being hashed beforehand.
+    Streaming,
+    /// Payload is not signed. Requires HTTPS base URL.
+    Unsigned,
+}
@@ -139,6 +139,15 @@ pub fn to_http_headers(map: &Multimap) -> Vec<String> {
     headers
 }
 
+/// Encodes object name for URL path and canonical URI as S3 expects; every byte other than
+/// unreserved characters is percent encoded except `/`.
+pub fn urlencode_path(path: &str) -> String {
+    path.split('/')
+        .map(|v| urlencode(v).into_owned())
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
 pub fn to_query_string(map: &Multimap) -> String {
     let mut query = String::new();
     for (key, values) in map.iter_all() {
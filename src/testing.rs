@@ -0,0 +1,1594 @@
+// MinIO Rust Library for Amazon S3 Compatible Cloud Storage
+// Copyright 2022 MinIO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! In-process S3 compatible server for tests
+//!
+//! [MockServer] keeps buckets and objects in memory and implements bucket, object, multipart,
+//! tagging, policy, versioning and listing APIs well enough to run [Client] end-to-end without a MinIO
+//! server. Requests are not authenticated. Enable with the `testing` feature.
+
+use crate::s3::checksum::{Checksum, ChecksumAlgorithm};
+use crate::s3::client::{Client, ClientBuilder};
+use crate::s3::creds::StaticProvider;
+use crate::s3::error::Error;
+use crate::s3::http::BaseUrl;
+use crate::s3::signer::STREAMING_PAYLOAD;
+use crate::s3::types::Part as ClientPart;
+use crate::s3::utils::{
+    get_default_text, get_option_text, md5sum_hash, to_http_header_value, to_iso8601utc, utc_now,
+    UtcTime,
+};
+use bytes::{Buf, Bytes};
+use hyper::header::HeaderMap;
+use hyper::http::response::Builder;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+use xmltree::Element;
+
+const XMLNS: &str = "http://s3.amazonaws.com/doc/2006-03-01/";
+const REGION: &str = "us-east-1";
+const MIN_PART_SIZE: usize = 5 * 1024 * 1024;
+
+/// Headers stored with an object and returned by GetObject/HeadObject.
+const STORED_HEADERS: [&str; 6] = [
+    "content-type",
+    "content-encoding",
+    "content-disposition",
+    "content-language",
+    "cache-control",
+    "expires",
+];
+
+#[derive(Clone, Debug)]
+struct ObjectVersion {
+    version_id: String,
+    delete_marker: bool,
+    data: Bytes,
+    etag: String,
+    last_modified: UtcTime,
+    headers: Vec<(String, String)>,
+    tags: BTreeMap<String, String>,
+    checksum: Option<Checksum>,
+}
+
+#[derive(Debug)]
+struct Part {
+    data: Bytes,
+    etag: String,
+    last_modified: UtcTime,
+    checksum: Option<Checksum>,
+}
+
+#[derive(Debug)]
+struct Upload {
+    object: String,
+    initiated: UtcTime,
+    headers: Vec<(String, String)>,
+    tags: BTreeMap<String, String>,
+    checksum_algorithm: Option<ChecksumAlgorithm>,
+    parts: BTreeMap<u16, Part>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    created: UtcTime,
+    versioning: Option<String>,
+    tags: Option<BTreeMap<String, String>>,
+    policy: Option<Bytes>,
+    // Versions of each object, oldest first.
+    objects: BTreeMap<String, Vec<ObjectVersion>>,
+    uploads: BTreeMap<String, Upload>,
+}
+
+impl Bucket {
+    fn versioning_enabled(&self) -> bool {
+        self.versioning.as_deref() == Some("Enabled")
+    }
+
+    fn get(&self, key: &str, version_id: Option<&str>) -> Result<&ObjectVersion, S3Error> {
+        let versions = self.objects.get(key).ok_or(S3Error::no_such_key())?;
+        let version = match version_id {
+            Some(id) => versions
+                .iter()
+                .find(|v| v.version_id == id)
+                .ok_or(S3Error::new(404, "NoSuchVersion", "version does not exist"))?,
+            None => versions.last().ok_or(S3Error::no_such_key())?,
+        };
+        if version.delete_marker {
+            return Err(S3Error::no_such_key());
+        }
+        Ok(version)
+    }
+
+    fn get_mut(
+        &mut self,
+        key: &str,
+        version_id: Option<&str>,
+    ) -> Result<&mut ObjectVersion, S3Error> {
+        let versions = self.objects.get_mut(key).ok_or(S3Error::no_such_key())?;
+        let version = match version_id {
+            Some(id) => versions.iter_mut().find(|v| v.version_id == id),
+            None => versions.last_mut(),
+        };
+        match version {
+            Some(v) if !v.delete_marker => Ok(v),
+            _ => Err(S3Error::no_such_key()),
+        }
+    }
+
+    // Stores a new version and returns its version ID.
+    fn put(&mut self, key: &str, mut version: ObjectVersion) -> String {
+        let versions = self.objects.entry(key.to_string()).or_default();
+        version.version_id = match self.versioning.as_deref() {
+            Some("Enabled") => random_id(),
+            _ => {
+                versions.retain(|v| v.version_id != "null");
+                String::from("null")
+            }
+        };
+        let version_id = version.version_id.clone();
+        versions.push(version);
+        version_id
+    }
+
+    // Removes given version or adds a delete marker; returns removed version ID, whether a
+    // delete marker was involved and version ID of the new delete marker.
+    fn delete(
+        &mut self,
+        key: &str,
+        version_id: Option<&str>,
+    ) -> (Option<String>, bool, Option<String>) {
+        let versions = self.objects.entry(key.to_string()).or_default();
+        let result = match version_id {
+            Some(id) => {
+                let marker = versions
+                    .iter()
+                    .any(|v| v.version_id == id && v.delete_marker);
+                versions.retain(|v| v.version_id != id);
+                (Some(id.to_string()), marker, None)
+            }
+            None => {
+                let marker_id = match self.versioning.as_deref() {
+                    Some("Enabled") => Some(random_id()),
+                    Some(_) => Some(String::from("null")),
+                    None => None,
+                };
+                versions.retain(|v| v.version_id != "null");
+                match marker_id {
+                    Some(id) => {
+                        versions.push(ObjectVersion {
+                            version_id: id.clone(),
+                            delete_marker: true,
+                            data: Bytes::new(),
+                            etag: String::new(),
+                            last_modified: utc_now(),
+                            headers: Vec::new(),
+                            tags: BTreeMap::new(),
+                            checksum: None,
+                        });
+                        (None, true, Some(id))
+                    }
+                    None => (None, false, None),
+                }
+            }
+        };
+        if versions.is_empty() {
+            self.objects.remove(key);
+        }
+        result
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    buckets: BTreeMap<String, Bucket>,
+}
+
+impl State {
+    fn bucket(&self, name: &str) -> Result<&Bucket, S3Error> {
+        self.buckets.get(name).ok_or(S3Error::no_such_bucket())
+    }
+
+    fn bucket_mut(&mut self, name: &str) -> Result<&mut Bucket, S3Error> {
+        self.buckets.get_mut(name).ok_or(S3Error::no_such_bucket())
+    }
+}
+
+#[derive(Debug)]
+struct S3Error {
+    status: u16,
+    code: &'static str,
+    message: &'static str,
+}
+
+impl S3Error {
+    fn new(status: u16, code: &'static str, message: &'static str) -> S3Error {
+        S3Error {
+            status,
+            code,
+            message,
+        }
+    }
+
+    fn no_such_bucket() -> S3Error {
+        S3Error::new(404, "NoSuchBucket", "The specified bucket does not exist")
+    }
+
+    fn no_such_key() -> S3Error {
+        S3Error::new(404, "NoSuchKey", "The specified key does not exist.")
+    }
+
+    fn no_such_upload() -> S3Error {
+        S3Error::new(404, "NoSuchUpload", "The specified upload does not exist.")
+    }
+
+    fn malformed_xml() -> S3Error {
+        S3Error::new(
+            400,
+            "MalformedXML",
+            "The XML you provided was not well-formed.",
+        )
+    }
+
+    fn not_implemented() -> S3Error {
+        S3Error::new(
+            501,
+            "NotImplemented",
+            "A header or query you provided implies functionality that is not implemented.",
+        )
+    }
+}
+
+struct MockRequest {
+    method: Method,
+    bucket: String,
+    object: String,
+    query: HashMap<String, String>,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl MockRequest {
+    fn query(&self, key: &str) -> Option<&str> {
+        self.query.get(key).map(|v| v.as_str())
+    }
+
+    fn header(&self, key: &str) -> Option<&str> {
+        self.headers.get(key).and_then(|v| v.to_str().ok())
+    }
+
+    fn xml_body(&self) -> Result<Element, S3Error> {
+        Element::parse(self.body.clone().reader()).map_err(|_| S3Error::malformed_xml())
+    }
+
+    fn check_content_md5(&self) -> Result<(), S3Error> {
+        match self.header("content-md5") {
+            Some(v) if v != md5sum_hash(&self.body) => Err(S3Error::new(
+                400,
+                "BadDigest",
+                "The Content-MD5 you specified did not match what we received.",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    // Returns x-amz-checksum-* header of the request after verifying it against the body.
+    fn checksum(&self) -> Result<Option<Checksum>, S3Error> {
+        let checksum = match Checksum::from_headers(&self.headers) {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        if checksum.algorithm.checksum(&self.body) != checksum.value {
+            return Err(S3Error::new(
+                400,
+                "XAmzContentChecksumMismatch",
+                "The provided 'x-amz-checksum' header does not match what was computed.",
+            ));
+        }
+        Ok(Some(checksum))
+    }
+
+    // Headers to store with an object created by this request.
+    fn object_headers(&self) -> Vec<(String, String)> {
+        let mut headers: Vec<(String, String)> = self
+            .headers
+            .iter()
+            .filter(|(k, _)| {
+                STORED_HEADERS.contains(&k.as_str()) || k.as_str().starts_with("x-amz-meta-")
+            })
+            .filter_map(|(k, v)| Some((k.to_string(), v.to_str().ok()?.to_string())))
+            .collect();
+        if !headers.iter().any(|(k, _)| k == "content-type") {
+            headers.push((
+                String::from("content-type"),
+                String::from("binary/octet-stream"),
+            ));
+        }
+        headers
+    }
+
+    fn object_tags(&self) -> BTreeMap<String, String> {
+        match self.header("x-amz-tagging") {
+            Some(v) => parse_query(v).into_iter().collect(),
+            None => BTreeMap::new(),
+        }
+    }
+}
+
+/// S3 compatible server listening on an ephemeral port of the loopback interface
+///
+/// The server runs on the current tokio runtime until it is dropped.
+#[derive(Debug)]
+pub struct MockServer {
+    addr: SocketAddr,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockServer {
+    pub const ACCESS_KEY: &'static str = "minioadmin";
+    pub const SECRET_KEY: &'static str = "minioadmin";
+
+    /// Starts a new server with no buckets.
+    pub async fn start() -> Result<MockServer, Error> {
+        let state = Arc::new(Mutex::new(State::default()));
+        let make_svc = make_service_fn(move |_| {
+            let state = state.clone();
+            async move { Ok::<_, Infallible>(service_fn(move |req| handle(state.clone(), req))) }
+        });
+
+        let server = Server::try_bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+            .map_err(std::io::Error::other)?
+            .serve(make_svc);
+        let addr = server.local_addr();
+        let (tx, rx) = oneshot::channel::<()>();
+        tokio::spawn(server.with_graceful_shutdown(async {
+            rx.await.ok();
+        }));
+
+        Ok(MockServer {
+            addr,
+            shutdown: Some(tx),
+        })
+    }
+
+    pub fn endpoint(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Returns base URL of the server with region set.
+    pub fn base_url(&self) -> BaseUrl {
+        let mut base_url = BaseUrl::from_string(self.endpoint()).unwrap();
+        base_url.region = String::from(REGION);
+        base_url
+    }
+
+    /// Returns client connected to the server.
+    pub fn client(&self) -> Result<Client, Error> {
+        ClientBuilder::new(self.base_url())
+            .provider(Some(Arc::new(StaticProvider::new(
+                MockServer::ACCESS_KEY,
+                MockServer::SECRET_KEY,
+                None,
+            ))))
+            .build()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(tx) = self.shutdown.take() {
+            tx.send(()).ok();
+        }
+    }
+}
+
+// Decodes aws-chunked body and drops aws-chunked from content encoding; chunk signatures
+// are not verified.
+fn decode_aws_chunked(mut body: &[u8], headers: &mut HeaderMap) -> Option<Bytes> {
+    let mut data = Vec::new();
+    loop {
+        let pos = body.windows(2).position(|w| w == b"\r\n")?;
+        let header = std::str::from_utf8(&body[..pos]).ok()?;
+        let size = usize::from_str_radix(header.split(';').next()?, 16).ok()?;
+        body = &body[pos + 2..];
+        if body.len() < size + 2 || &body[size..size + 2] != b"\r\n" {
+            return None;
+        }
+        data.extend_from_slice(&body[..size]);
+        body = &body[size + 2..];
+        if size == 0 {
+            break;
+        }
+    }
+
+    let decoded_length = headers.get("x-amz-decoded-content-length")?.to_str().ok()?;
+    if decoded_length.parse::<usize>().ok()? != data.len() {
+        return None;
+    }
+
+    let encoding = headers
+        .get("content-encoding")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .split(',')
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty() && *v != "aws-chunked")
+        .collect::<Vec<&str>>()
+        .join(",");
+    if encoding.is_empty() {
+        headers.remove("content-encoding");
+    } else {
+        headers.insert("content-encoding", encoding.parse().ok()?);
+    }
+
+    Some(Bytes::from(data))
+}
+
+async fn handle(
+    state: Arc<Mutex<State>>,
+    req: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    let (parts, body) = req.into_parts();
+    let path = urlencoding::decode(parts.uri.path())
+        .map(|v| v.into_owned())
+        .unwrap_or_else(|_| parts.uri.path().to_string());
+    let (bucket, object) = match path.trim_start_matches('/').split_once('/') {
+        Some((b, o)) => (b.to_string(), o.to_string()),
+        None => (path.trim_start_matches('/').to_string(), String::new()),
+    };
+    let resource = path.clone();
+
+    let body = match hyper::body::to_bytes(body).await {
+        Ok(v) => v,
+        Err(_) => {
+            return Ok(error_response(
+                S3Error::new(400, "IncompleteBody", "request body could not be read"),
+                &resource,
+            ))
+        }
+    };
+
+    let mut headers = parts.headers;
+    let body = if headers
+        .get("x-amz-content-sha256")
+        .is_some_and(|v| v == STREAMING_PAYLOAD)
+    {
+        match decode_aws_chunked(&body, &mut headers) {
+            Some(v) => v,
+            None => {
+                return Ok(error_response(
+                    S3Error::new(
+                        400,
+                        "IncompleteBody",
+                        "aws-chunked body could not be decoded",
+                    ),
+                    &resource,
+                ))
+            }
+        }
+    } else {
+        body
+    };
+
+    let req = MockRequest {
+        method: parts.method,
+        bucket,
+        object,
+        query: parse_query(parts.uri.query().unwrap_or_default())
+            .into_iter()
+            .collect(),
+        headers,
+        body,
+    };
+
+    let resp = {
+        let mut state = state.lock().unwrap();
+        route(&mut state, &req)
+    };
+    Ok(match resp {
+        Ok(v) => v,
+        Err(e) => error_response(e, &resource),
+    })
+}
+
+fn route(state: &mut State, req: &MockRequest) -> Result<Response<Body>, S3Error> {
+    if req.bucket.is_empty() {
+        return match req.method {
+            Method::GET => list_buckets(state),
+            _ => Err(S3Error::not_implemented()),
+        };
+    }
+
+    if req.object.is_empty() {
+        return match req.method {
+            Method::PUT if req.query.contains_key("versioning") => {
+                put_bucket_versioning(state, req)
+            }
+            Method::PUT if req.query.contains_key("tagging") => put_bucket_tagging(state, req),
+            Method::PUT if req.query.contains_key("policy") => put_bucket_policy(state, req),
+            Method::PUT if req.query.is_empty() => make_bucket(state, req),
+            Method::HEAD => state.bucket(&req.bucket).map(|_| empty(200)),
+            Method::DELETE if req.query.contains_key("tagging") => {
+                state.bucket_mut(&req.bucket)?.tags = None;
+                Ok(empty(204))
+            }
+            Method::DELETE if req.query.contains_key("policy") => {
+                state.bucket_mut(&req.bucket)?.policy = None;
+                Ok(empty(204))
+            }
+            Method::DELETE if req.query.is_empty() => remove_bucket(state, req),
+            Method::GET if req.query.contains_key("location") => {
+                state.bucket(&req.bucket)?;
+                Ok(xml(format!(
+                    "<LocationConstraint xmlns=\"{}\">{}</LocationConstraint>",
+                    XMLNS, REGION
+                )))
+            }
+            Method::GET if req.query.contains_key("versioning") => {
+                get_bucket_versioning(state, req)
+            }
+            Method::GET if req.query.contains_key("tagging") => {
+                match &state.bucket(&req.bucket)?.tags {
+                    Some(tags) => Ok(xml(tagging_xml(tags))),
+                    None => Err(S3Error::new(
+                        404,
+                        "NoSuchTagSet",
+                        "The TagSet does not exist",
+                    )),
+                }
+            }
+            Method::GET if req.query.contains_key("policy") => {
+                match &state.bucket(&req.bucket)?.policy {
+                    Some(policy) => Ok(Response::builder()
+                        .status(200)
+                        .header("Content-Type", "application/json")
+                        .body(Body::from(policy.clone()))
+                        .unwrap()),
+                    None => Err(S3Error::new(
+                        404,
+                        "NoSuchBucketPolicy",
+                        "The bucket policy does not exist",
+                    )),
+                }
+            }
+            Method::GET if req.query.contains_key("uploads") => list_multipart_uploads(state, req),
+            Method::GET if req.query.contains_key("versions") => list_object_versions(state, req),
+            Method::GET => list_objects(state, req),
+            Method::POST if req.query.contains_key("delete") => delete_objects(state, req),
+            _ => Err(S3Error::not_implemented()),
+        };
+    }
+
+    match req.method {
+        Method::PUT if req.query.contains_key("uploadId") => upload_part(state, req),
+        Method::PUT if req.query.contains_key("tagging") => put_object_tagging(state, req),
+        Method::PUT if req.header("x-amz-copy-source").is_some() => copy_object(state, req),
+        Method::PUT => put_object(state, req),
+        Method::GET if req.query.contains_key("uploadId") => list_parts(state, req),
+        Method::GET if req.query.contains_key("tagging") => {
+            let bucket = state.bucket(&req.bucket)?;
+            let version = bucket.get(&req.object, req.query("versionId"))?;
+            Ok(xml(tagging_xml(&version.tags)))
+        }
+        Method::GET | Method::HEAD => get_object(state, req),
+        Method::DELETE if req.query.contains_key("uploadId") => {
+            let bucket = state.bucket_mut(&req.bucket)?;
+            let upload_id = req.query("uploadId").unwrap_or_default();
+            match bucket.uploads.remove(upload_id) {
+                Some(_) => Ok(empty(204)),
+                None => Err(S3Error::no_such_upload()),
+            }
+        }
+        Method::DELETE if req.query.contains_key("tagging") => {
+            let bucket = state.bucket_mut(&req.bucket)?;
+            bucket
+                .get_mut(&req.object, req.query("versionId"))?
+                .tags
+                .clear();
+            Ok(empty(204))
+        }
+        Method::DELETE => delete_object(state, req),
+        Method::POST if req.query.contains_key("uploads") => create_multipart_upload(state, req),
+        Method::POST if req.query.contains_key("uploadId") => complete_multipart_upload(state, req),
+        _ => Err(S3Error::not_implemented()),
+    }
+}
+
+fn list_buckets(state: &State) -> Result<Response<Body>, S3Error> {
+    let mut data = format!(
+        "<ListAllMyBucketsResult xmlns=\"{}\"><Owner><ID>minio</ID><DisplayName>minio</DisplayName></Owner><Buckets>",
+        XMLNS
+    );
+    for (name, bucket) in state.buckets.iter() {
+        data.push_str(&format!(
+            "<Bucket><Name>{}</Name><CreationDate>{}</CreationDate></Bucket>",
+            escape(name),
+            to_iso8601utc(bucket.created)
+        ));
+    }
+    data.push_str("</Buckets></ListAllMyBucketsResult>");
+    Ok(xml(data))
+}
+
+fn make_bucket(state: &mut State, req: &MockRequest) -> Result<Response<Body>, S3Error> {
+    if state.buckets.contains_key(&req.bucket) {
+        return Err(S3Error::new(
+            409,
+            "BucketAlreadyOwnedByYou",
+            "Your previous request to create the named bucket succeeded and you already own it.",
+        ));
+    }
+
+    state.buckets.insert(
+        req.bucket.clone(),
+        Bucket {
+            created: utc_now(),
+            versioning: None,
+            tags: None,
+            policy: None,
+            objects: BTreeMap::new(),
+            uploads: BTreeMap::new(),
+        },
+    );
+    Ok(Response::builder()
+        .status(200)
+        .header("Location", format!("/{}", req.bucket))
+        .body(Body::empty())
+        .unwrap())
+}
+
+fn remove_bucket(state: &mut State, req: &MockRequest) -> Result<Response<Body>, S3Error> {
+    if !state.bucket(&req.bucket)?.objects.is_empty() {
+        return Err(S3Error::new(
+            409,
+            "BucketNotEmpty",
+            "The bucket you tried to delete is not empty",
+        ));
+    }
+
+    state.buckets.remove(&req.bucket);
+    Ok(empty(204))
+}
+
+fn get_bucket_versioning(state: &State, req: &MockRequest) -> Result<Response<Body>, S3Error> {
+    let bucket = state.bucket(&req.bucket)?;
+    let mut data = format!("<VersioningConfiguration xmlns=\"{}\">", XMLNS);
+    if let Some(v) = &bucket.versioning {
+        data.push_str(&format!("<Status>{}</Status>", v));
+    }
+    data.push_str("</VersioningConfiguration>");
+    Ok(xml(data))
+}
+
+fn put_bucket_versioning(state: &mut State, req: &MockRequest) -> Result<Response<Body>, S3Error> {
+    let root = req.xml_body()?;
+    let status = get_default_text(&root, "Status");
+    if status != "Enabled" && status != "Suspended" {
+        return Err(S3Error::malformed_xml());
+    }
+
+    state.bucket_mut(&req.bucket)?.versioning = Some(status);
+    Ok(empty(200))
+}
+
+fn put_bucket_tagging(state: &mut State, req: &MockRequest) -> Result<Response<Body>, S3Error> {
+    let tags = parse_tagging(&req.xml_body()?)?;
+    state.bucket_mut(&req.bucket)?.tags = Some(tags);
+    Ok(empty(204))
+}
+
+fn put_bucket_policy(state: &mut State, req: &MockRequest) -> Result<Response<Body>, S3Error> {
+    if serde_json::from_slice::<serde_json::Value>(&req.body).is_err() {
+        return Err(S3Error::new(
+            400,
+            "MalformedPolicy",
+            "Policies must be valid JSON and the first byte must be '{'",
+        ));
+    }
+
+    state.bucket_mut(&req.bucket)?.policy = Some(req.body.clone());
+    Ok(empty(204))
+}
+
+fn put_object_tagging(state: &mut State, req: &MockRequest) -> Result<Response<Body>, S3Error> {
+    let tags = parse_tagging(&req.xml_body()?)?;
+    let bucket = state.bucket_mut(&req.bucket)?;
+    bucket.get_mut(&req.object, req.query("versionId"))?.tags = tags;
+    Ok(empty(200))
+}
+
+fn put_object(state: &mut State, req: &MockRequest) -> Result<Response<Body>, S3Error> {
+    req.check_content_md5()?;
+    let checksum = req.checksum()?;
+    let bucket = state.bucket_mut(&req.bucket)?;
+    let etag = format!("{:x}", md5::compute(&req.body));
+    let version_id = bucket.put(
+        &req.object,
+        ObjectVersion {
+            version_id: String::new(),
+            delete_marker: false,
+            data: req.body.clone(),
+            etag: etag.clone(),
+            last_modified: utc_now(),
+            headers: req.object_headers(),
+            tags: req.object_tags(),
+            checksum: checksum.clone(),
+        },
+    );
+
+    let mut builder = Response::builder()
+        .status(200)
+        .header("ETag", format!("\"{}\"", etag));
+    if let Some(v) = checksum {
+        builder = builder.header(v.algorithm.header_name(), v.value);
+    }
+    if bucket.versioning_enabled() {
+        builder = builder.header("x-amz-version-id", version_id);
+    }
+    Ok(builder.body(Body::empty()).unwrap())
+}
+
+// Returns data of x-amz-copy-source object limited to x-amz-copy-source-range.
+fn copy_source(state: &State, req: &MockRequest) -> Result<(ObjectVersion, Bytes), S3Error> {
+    let source = req.header("x-amz-copy-source").unwrap_or_default();
+    let (path, version_id) = match source.split_once("?versionId=") {
+        Some((p, v)) => (p, Some(v)),
+        None => (source, None),
+    };
+    let decode = |s: &str| {
+        urlencoding::decode(s)
+            .map(|v| v.into_owned())
+            .unwrap_or_else(|_| s.to_string())
+    };
+    let (path, version_id) = (decode(path), version_id.map(decode));
+    let (bucket, object) = path
+        .trim_start_matches('/')
+        .split_once('/')
+        .ok_or(S3Error::new(
+            400,
+            "InvalidArgument",
+            "Copy Source must mention the source bucket and key",
+        ))?;
+
+    let version = state.bucket(bucket)?.get(object, version_id.as_deref())?;
+    if let Some(v) = req.header("x-amz-copy-source-if-match") {
+        if v.trim_matches('"') != version.etag {
+            return Err(precondition_failed());
+        }
+    }
+    if let Some(v) = req.header("x-amz-copy-source-if-none-match") {
+        if v.trim_matches('"') == version.etag {
+            return Err(precondition_failed());
+        }
+    }
+
+    let data = match req.header("x-amz-copy-source-range") {
+        Some(v) => {
+            let (start, end) = parse_range(v, version.data.len())?;
+            version.data.slice(start..=end)
+        }
+        None => version.data.clone(),
+    };
+    Ok((version.clone(), data))
+}
+
+fn copy_object(state: &mut State, req: &MockRequest) -> Result<Response<Body>, S3Error> {
+    let (source, data) = copy_source(state, req)?;
+    let headers = match req.header("x-amz-metadata-directive") {
+        Some("REPLACE") => req.object_headers(),
+        _ => source.headers,
+    };
+    let tags = match req.header("x-amz-tagging-directive") {
+        Some("REPLACE") => req.object_tags(),
+        _ => source.tags,
+    };
+
+    let bucket = state.bucket_mut(&req.bucket)?;
+    let etag = format!("{:x}", md5::compute(&data));
+    let last_modified = utc_now();
+    let version_id = bucket.put(
+        &req.object,
+        ObjectVersion {
+            version_id: String::new(),
+            delete_marker: false,
+            data,
+            etag: etag.clone(),
+            last_modified,
+            headers,
+            tags,
+            checksum: None,
+        },
+    );
+
+    let mut builder = Response::builder().status(200);
+    if bucket.versioning_enabled() {
+        builder = builder.header("x-amz-version-id", version_id);
+    }
+    Ok(xml_with(
+        builder,
+        format!(
+            "<CopyObjectResult xmlns=\"{}\"><LastModified>{}</LastModified><ETag>\"{}\"</ETag></CopyObjectResult>",
+            XMLNS,
+            to_iso8601utc(last_modified),
+            etag
+        ),
+    ))
+}
+
+fn get_object(state: &State, req: &MockRequest) -> Result<Response<Body>, S3Error> {
+    let bucket = state.bucket(&req.bucket)?;
+    let version = bucket.get(&req.object, req.query("versionId"))?;
+
+    if let Some(v) = req.header("if-match") {
+        if v.trim_matches('"') != version.etag {
+            return Err(precondition_failed());
+        }
+    }
+    if let Some(v) = req.header("if-none-match") {
+        if v.trim_matches('"') == version.etag {
+            return Ok(empty(304));
+        }
+    }
+
+    let mut builder = Response::builder()
+        .header("ETag", format!("\"{}\"", version.etag))
+        .header("Last-Modified", to_http_header_value(version.last_modified))
+        .header("Accept-Ranges", "bytes");
+    for (key, value) in version.headers.iter() {
+        builder = builder.header(key, value);
+    }
+    if version.version_id != "null" {
+        builder = builder.header("x-amz-version-id", &version.version_id);
+    }
+    if !version.tags.is_empty() {
+        builder = builder.header("x-amz-tagging-count", version.tags.len());
+    }
+    if let Some(v) = &version.checksum {
+        if req.header("x-amz-checksum-mode") == Some("ENABLED") && req.header("range").is_none() {
+            builder = builder.header(v.algorithm.header_name(), &v.value);
+        }
+    }
+
+    let data = match req.header("range") {
+        Some(v) => {
+            let (start, end) = parse_range(v, version.data.len())?;
+            builder = builder.status(206).header(
+                "Content-Range",
+                format!("bytes {}-{}/{}", start, end, version.data.len()),
+            );
+            version.data.slice(start..=end)
+        }
+        None => {
+            builder = builder.status(200);
+            version.data.clone()
+        }
+    };
+
+    builder = builder.header("Content-Length", data.len());
+    let body = match req.method {
+        Method::HEAD => Body::empty(),
+        _ => Body::from(data),
+    };
+    Ok(builder.body(body).unwrap())
+}
+
+fn delete_object(state: &mut State, req: &MockRequest) -> Result<Response<Body>, S3Error> {
+    let bucket = state.bucket_mut(&req.bucket)?;
+    let (version_id, delete_marker, marker_id) = bucket.delete(&req.object, req.query("versionId"));
+
+    let mut builder = Response::builder().status(204);
+    if delete_marker {
+        builder = builder.header("x-amz-delete-marker", "true");
+    }
+    if let Some(v) = marker_id.or(version_id) {
+        builder = builder.header("x-amz-version-id", v);
+    }
+    Ok(builder.body(Body::empty()).unwrap())
+}
+
+fn delete_objects(state: &mut State, req: &MockRequest) -> Result<Response<Body>, S3Error> {
+    req.check_content_md5()?;
+    let mut root = req.xml_body()?;
+    let quiet = get_default_text(&root, "Quiet").to_lowercase() == "true";
+    let bucket = state.bucket_mut(&req.bucket)?;
+
+    let mut data = format!("<DeleteResult xmlns=\"{}\">", XMLNS);
+    while let Some(object) = root.take_child("Object") {
+        let key = get_default_text(&object, "Key");
+        let (version_id, delete_marker, marker_id) =
+            bucket.delete(&key, get_option_text(&object, "VersionId").as_deref());
+        if quiet {
+            continue;
+        }
+
+        data.push_str(&format!("<Deleted><Key>{}</Key>", escape(&key)));
+        if let Some(v) = version_id {
+            data.push_str(&format!("<VersionId>{}</VersionId>", v));
+        }
+        data.push_str(&format!("<DeleteMarker>{}</DeleteMarker>", delete_marker));
+        if let Some(v) = marker_id {
+            data.push_str(&format!(
+                "<DeleteMarkerVersionId>{}</DeleteMarkerVersionId>",
+                v
+            ));
+        }
+        data.push_str("</Deleted>");
+    }
+    data.push_str("</DeleteResult>");
+    Ok(xml(data))
+}
+
+fn create_multipart_upload(
+    state: &mut State,
+    req: &MockRequest,
+) -> Result<Response<Body>, S3Error> {
+    let bucket = state.bucket_mut(&req.bucket)?;
+    let upload_id = random_id();
+    bucket.uploads.insert(
+        upload_id.clone(),
+        Upload {
+            object: req.object.clone(),
+            initiated: utc_now(),
+            headers: req.object_headers(),
+            tags: req.object_tags(),
+            checksum_algorithm: req
+                .header("x-amz-checksum-algorithm")
+                .and_then(|v| ChecksumAlgorithm::parse(v).ok()),
+            parts: BTreeMap::new(),
+        },
+    );
+
+    Ok(xml(format!(
+        "<InitiateMultipartUploadResult xmlns=\"{}\"><Bucket>{}</Bucket><Key>{}</Key><UploadId>{}</UploadId></InitiateMultipartUploadResult>",
+        XMLNS,
+        escape(&req.bucket),
+        escape(&req.object),
+        upload_id
+    )))
+}
+
+fn upload_part(state: &mut State, req: &MockRequest) -> Result<Response<Body>, S3Error> {
+    let part_number = req
+        .query("partNumber")
+        .and_then(|v| v.parse::<u16>().ok())
+        .filter(|v| (1..=10000).contains(v))
+        .ok_or(S3Error::new(
+            400,
+            "InvalidArgument",
+            "Part number must be an integer between 1 and 10000, inclusive",
+        ))?;
+
+    let (copy, checksum) = match req.header("x-amz-copy-source") {
+        Some(_) => (Some(copy_source(state, req)?.1), None),
+        None => {
+            req.check_content_md5()?;
+            (None, req.checksum()?)
+        }
+    };
+
+    let bucket = state.bucket_mut(&req.bucket)?;
+    let upload = bucket
+        .uploads
+        .get_mut(req.query("uploadId").unwrap_or_default())
+        .filter(|v| v.object == req.object)
+        .ok_or(S3Error::no_such_upload())?;
+
+    let data = copy.clone().unwrap_or_else(|| req.body.clone());
+    let etag = format!("{:x}", md5::compute(&data));
+    let last_modified = utc_now();
+    upload.parts.insert(
+        part_number,
+        Part {
+            data,
+            etag: etag.clone(),
+            last_modified,
+            checksum: checksum.clone(),
+        },
+    );
+
+    match copy {
+        Some(_) => Ok(xml(format!(
+            "<CopyPartResult xmlns=\"{}\"><LastModified>{}</LastModified><ETag>\"{}\"</ETag></CopyPartResult>",
+            XMLNS,
+            to_iso8601utc(last_modified),
+            etag
+        ))),
+        None => {
+            let mut builder = Response::builder()
+                .status(200)
+                .header("ETag", format!("\"{}\"", etag));
+            if let Some(v) = checksum {
+                builder = builder.header(v.algorithm.header_name(), v.value);
+            }
+            Ok(builder.body(Body::empty()).unwrap())
+        }
+    }
+}
+
+fn complete_multipart_upload(
+    state: &mut State,
+    req: &MockRequest,
+) -> Result<Response<Body>, S3Error> {
+    let mut root = req.xml_body()?;
+    let bucket = state.bucket_mut(&req.bucket)?;
+    let upload_id = req.query("uploadId").unwrap_or_default();
+    let upload = bucket
+        .uploads
+        .get(upload_id)
+        .filter(|v| v.object == req.object)
+        .ok_or(S3Error::no_such_upload())?;
+
+    let algorithm = upload.checksum_algorithm;
+    let mut requested: Vec<(u16, String, Option<String>)> = Vec::new();
+    while let Some(part) = root.take_child("Part") {
+        let number = get_default_text(&part, "PartNumber")
+            .parse::<u16>()
+            .map_err(|_| S3Error::malformed_xml())?;
+        if requested.last().is_some_and(|(n, _, _)| *n >= number) {
+            return Err(S3Error::new(
+                400,
+                "InvalidPartOrder",
+                "The list of parts was not in ascending order.",
+            ));
+        }
+        let checksum = algorithm.and_then(|a| get_option_text(&part, &a.xml_tag()));
+        requested.push((number, get_default_text(&part, "ETag"), checksum));
+    }
+    if requested.is_empty() {
+        return Err(S3Error::malformed_xml());
+    }
+
+    let mut data: Vec<u8> = Vec::new();
+    let mut digests: Vec<u8> = Vec::new();
+    let mut parts: Vec<ClientPart> = Vec::new();
+    for (i, (number, etag, checksum)) in requested.iter().enumerate() {
+        let part = match upload.parts.get(number) {
+            Some(v)
+                if v.etag == etag.trim_matches('"')
+                    && (algorithm.is_none()
+                        || checksum.as_ref() == v.checksum.as_ref().map(|c| &c.value)) =>
+            {
+                v
+            }
+            _ => {
+                return Err(S3Error::new(
+                    400,
+                    "InvalidPart",
+                    "One or more of the specified parts could not be found.",
+                ))
+            }
+        };
+        if part.data.len() < MIN_PART_SIZE && i + 1 < requested.len() {
+            return Err(S3Error::new(
+                400,
+                "EntityTooSmall",
+                "Your proposed upload is smaller than the minimum allowed object size.",
+            ));
+        }
+        data.extend_from_slice(&part.data);
+        digests.extend_from_slice(&md5::compute(&part.data).0);
+        parts.push(ClientPart {
+            number: *number,
+            etag: etag.clone(),
+            checksum: checksum.clone(),
+        });
+    }
+    let checksum = algorithm.and_then(|a| {
+        Some(Checksum {
+            algorithm: a,
+            value: a.composite_checksum(&parts)?,
+        })
+    });
+
+    let etag = format!("{:x}-{}", md5::compute(&digests), requested.len());
+    let upload = bucket.uploads.remove(upload_id).unwrap();
+    let version_id = bucket.put(
+        &req.object,
+        ObjectVersion {
+            version_id: String::new(),
+            delete_marker: false,
+            data: Bytes::from(data),
+            etag: etag.clone(),
+            last_modified: utc_now(),
+            headers: upload.headers,
+            tags: upload.tags,
+            checksum: checksum.clone(),
+        },
+    );
+
+    let mut builder = Response::builder().status(200);
+    if bucket.versioning_enabled() {
+        builder = builder.header("x-amz-version-id", version_id);
+    }
+    Ok(xml_with(
+        builder,
+        format!(
+            "<CompleteMultipartUploadResult xmlns=\"{}\"><Location>/{}/{}</Location><Bucket>{}</Bucket><Key>{}</Key><ETag>\"{}\"</ETag>{}</CompleteMultipartUploadResult>",
+            XMLNS,
+            escape(&req.bucket),
+            escape(&req.object),
+            escape(&req.bucket),
+            escape(&req.object),
+            etag,
+            checksum
+                .map(|v| format!("<{0}>{1}</{0}>", v.algorithm.xml_tag(), v.value))
+                .unwrap_or_default()
+        ),
+    ))
+}
+
+fn list_parts(state: &State, req: &MockRequest) -> Result<Response<Body>, S3Error> {
+    let bucket = state.bucket(&req.bucket)?;
+    let upload_id = req.query("uploadId").unwrap_or_default();
+    let upload = bucket
+        .uploads
+        .get(upload_id)
+        .filter(|v| v.object == req.object)
+        .ok_or(S3Error::no_such_upload())?;
+    let marker = req
+        .query("part-number-marker")
+        .and_then(|v| v.parse::<u16>().ok())
+        .unwrap_or(0);
+    let max_parts = req
+        .query("max-parts")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(1000)
+        .min(1000);
+
+    let mut parts = upload.parts.range(marker + 1..).peekable();
+    let mut data = String::new();
+    let mut next_marker = marker;
+    for _ in 0..max_parts {
+        let (number, part) = match parts.next() {
+            Some(v) => v,
+            None => break,
+        };
+        data.push_str(&format!(
+            "<Part><PartNumber>{}</PartNumber><LastModified>{}</LastModified><ETag>\"{}\"</ETag><Size>{}</Size></Part>",
+            number,
+            to_iso8601utc(part.last_modified),
+            part.etag,
+            part.data.len()
+        ));
+        next_marker = *number;
+    }
+
+    Ok(xml(format!(
+        "<ListPartsResult xmlns=\"{}\"><Bucket>{}</Bucket><Key>{}</Key><UploadId>{}</UploadId><StorageClass>STANDARD</StorageClass><PartNumberMarker>{}</PartNumberMarker><NextPartNumberMarker>{}</NextPartNumberMarker><MaxParts>{}</MaxParts><IsTruncated>{}</IsTruncated>{}</ListPartsResult>",
+        XMLNS,
+        escape(&req.bucket),
+        escape(&req.object),
+        upload_id,
+        marker,
+        next_marker,
+        max_parts,
+        parts.peek().is_some(),
+        data
+    )))
+}
+
+fn list_multipart_uploads(state: &State, req: &MockRequest) -> Result<Response<Body>, S3Error> {
+    let bucket = state.bucket(&req.bucket)?;
+    let prefix = req.query("prefix").unwrap_or_default();
+
+    let mut uploads: Vec<(&String, &Upload)> = bucket
+        .uploads
+        .iter()
+        .filter(|(_, v)| v.object.starts_with(prefix))
+        .collect();
+    uploads.sort_by(|a, b| (&a.1.object, a.1.initiated).cmp(&(&b.1.object, b.1.initiated)));
+
+    let mut data = format!(
+        "<ListMultipartUploadsResult xmlns=\"{}\"><Bucket>{}</Bucket><KeyMarker></KeyMarker><UploadIdMarker></UploadIdMarker><Prefix>{}</Prefix><MaxUploads>1000</MaxUploads><IsTruncated>false</IsTruncated>",
+        XMLNS,
+        escape(&req.bucket),
+        escape(prefix)
+    );
+    for (upload_id, upload) in uploads {
+        data.push_str(&format!(
+            "<Upload><Key>{}</Key><UploadId>{}</UploadId><Initiated>{}</Initiated><StorageClass>STANDARD</StorageClass></Upload>",
+            escape(&upload.object),
+            upload_id,
+            to_iso8601utc(upload.initiated)
+        ));
+    }
+    data.push_str("</ListMultipartUploadsResult>");
+    Ok(xml(data))
+}
+
+// Page of a listing after applying prefix, delimiter and max-keys.
+struct Page<'a, T> {
+    entries: Vec<(&'a str, T)>,
+    prefixes: Vec<&'a str>,
+    truncated: bool,
+    next_marker: Option<&'a str>,
+}
+
+fn paginate<'a, T>(
+    items: impl Iterator<Item = (&'a str, T)>,
+    prefix: &str,
+    delimiter: &str,
+    max_keys: usize,
+) -> Page<'a, T> {
+    let mut page = Page {
+        entries: Vec::new(),
+        prefixes: Vec::new(),
+        truncated: false,
+        next_marker: None,
+    };
+
+    for (key, item) in items.filter(|(k, _)| k.starts_with(prefix)) {
+        let common_prefix = match delimiter.is_empty() {
+            true => None,
+            false => key[prefix.len()..]
+                .find(delimiter)
+                .map(|i| &key[..prefix.len() + i + delimiter.len()]),
+        };
+        if common_prefix.is_some() && page.prefixes.last() == common_prefix.as_ref() {
+            continue;
+        }
+        if page.entries.len() + page.prefixes.len() >= max_keys {
+            page.truncated = max_keys > 0;
+            break;
+        }
+
+        match common_prefix {
+            Some(v) => {
+                page.prefixes.push(v);
+                page.next_marker = Some(v);
+            }
+            None => {
+                page.entries.push((key, item));
+                page.next_marker = Some(key);
+            }
+        }
+    }
+
+    page
+}
+
+// Returns whether key comes after the marker of a previous page.
+fn after_marker(key: &str, marker: &str, delimiter: &str) -> bool {
+    key > marker
+        && (delimiter.is_empty() || !marker.ends_with(delimiter) || !key.starts_with(marker))
+}
+
+fn encode(value: &str, url_encoding: bool) -> String {
+    match url_encoding {
+        true => urlencoding::encode(value).into_owned(),
+        false => escape(value),
+    }
+}
+
+fn list_objects(state: &State, req: &MockRequest) -> Result<Response<Body>, S3Error> {
+    let bucket = state.bucket(&req.bucket)?;
+    let v2 = req.query("list-type") == Some("2");
+    let prefix = req.query("prefix").unwrap_or_default();
+    let delimiter = req.query("delimiter").unwrap_or_default();
+    let url_encoding = req.query("encoding-type") == Some("url");
+    let max_keys = req
+        .query("max-keys")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(1000)
+        .min(1000);
+    let marker = match v2 {
+        true => req
+            .query("continuation-token")
+            .or(req.query("start-after"))
+            .unwrap_or_default(),
+        false => req.query("marker").unwrap_or_default(),
+    };
+
+    let items = bucket
+        .objects
+        .iter()
+        .filter(|(k, _)| after_marker(k, marker, delimiter))
+        .filter_map(|(k, v)| Some((k.as_str(), v.last().filter(|v| !v.delete_marker)?)));
+    let page = paginate(items, prefix, delimiter, max_keys);
+
+    let mut data = format!(
+        "<ListBucketResult xmlns=\"{}\"><Name>{}</Name><Prefix>{}</Prefix><MaxKeys>{}</MaxKeys><Delimiter>{}</Delimiter><IsTruncated>{}</IsTruncated>",
+        XMLNS,
+        escape(&req.bucket),
+        encode(prefix, url_encoding),
+        max_keys,
+        encode(delimiter, url_encoding),
+        page.truncated
+    );
+    if url_encoding {
+        data.push_str("<EncodingType>url</EncodingType>");
+    }
+    if v2 {
+        data.push_str(&format!(
+            "<KeyCount>{}</KeyCount>",
+            page.entries.len() + page.prefixes.len()
+        ));
+        if let Some(v) = req.query("start-after") {
+            data.push_str(&format!(
+                "<StartAfter>{}</StartAfter>",
+                encode(v, url_encoding)
+            ));
+        }
+        if let Some(v) = req.query("continuation-token") {
+            data.push_str(&format!(
+                "<ContinuationToken>{}</ContinuationToken>",
+                escape(v)
+            ));
+        }
+    } else {
+        data.push_str(&format!(
+            "<Marker>{}</Marker>",
+            encode(marker, url_encoding)
+        ));
+    }
+    if let (true, Some(v)) = (page.truncated, page.next_marker) {
+        match v2 {
+            true => data.push_str(&format!(
+                "<NextContinuationToken>{}</NextContinuationToken>",
+                escape(v)
+            )),
+            false => data.push_str(&format!(
+                "<NextMarker>{}</NextMarker>",
+                encode(v, url_encoding)
+            )),
+        }
+    }
+
+    for (key, version) in page.entries {
+        data.push_str(&format!(
+            "<Contents><Key>{}</Key><LastModified>{}</LastModified><ETag>\"{}\"</ETag><Size>{}</Size><StorageClass>STANDARD</StorageClass></Contents>",
+            encode(key, url_encoding),
+            to_iso8601utc(version.last_modified),
+            version.etag,
+            version.data.len()
+        ));
+    }
+    for prefix in page.prefixes {
+        data.push_str(&format!(
+            "<CommonPrefixes><Prefix>{}</Prefix></CommonPrefixes>",
+            encode(prefix, url_encoding)
+        ));
+    }
+    data.push_str("</ListBucketResult>");
+    Ok(xml(data))
+}
+
+fn list_object_versions(state: &State, req: &MockRequest) -> Result<Response<Body>, S3Error> {
+    let bucket = state.bucket(&req.bucket)?;
+    let prefix = req.query("prefix").unwrap_or_default();
+    let delimiter = req.query("delimiter").unwrap_or_default();
+    let url_encoding = req.query("encoding-type") == Some("url");
+    let max_keys = req
+        .query("max-keys")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(1000)
+        .min(1000);
+    let key_marker = req.query("key-marker").unwrap_or_default();
+    let version_id_marker = req.query("version-id-marker").unwrap_or_default();
+
+    // Versions are listed newest first; entries of key-marker are skipped up to and
+    // including version-id-marker.
+    let mut skipping = !version_id_marker.is_empty();
+    let items = bucket
+        .objects
+        .iter()
+        .flat_map(|(k, v)| {
+            v.iter()
+                .rev()
+                .enumerate()
+                .map(move |(i, v)| (k.as_str(), (i == 0, v)))
+        })
+        .filter(|(k, (_, v))| {
+            if key_marker.is_empty() {
+                return true;
+            }
+            if *k == key_marker && !version_id_marker.is_empty() {
+                if skipping {
+                    skipping = v.version_id != version_id_marker;
+                    return false;
+                }
+                return true;
+            }
+            after_marker(k, key_marker, delimiter)
+        });
+    let page = paginate(items, prefix, delimiter, max_keys);
+
+    let mut data = format!(
+        "<ListVersionsResult xmlns=\"{}\"><Name>{}</Name><Prefix>{}</Prefix><KeyMarker>{}</KeyMarker><VersionIdMarker>{}</VersionIdMarker><MaxKeys>{}</MaxKeys><Delimiter>{}</Delimiter><IsTruncated>{}</IsTruncated>",
+        XMLNS,
+        escape(&req.bucket),
+        encode(prefix, url_encoding),
+        encode(key_marker, url_encoding),
+        escape(version_id_marker),
+        max_keys,
+        encode(delimiter, url_encoding),
+        page.truncated
+    );
+    if url_encoding {
+        data.push_str("<EncodingType>url</EncodingType>");
+    }
+    if let (true, Some(v)) = (page.truncated, page.next_marker) {
+        data.push_str(&format!(
+            "<NextKeyMarker>{}</NextKeyMarker>",
+            encode(v, url_encoding)
+        ));
+        if let Some((k, (_, version))) = page.entries.last() {
+            if *k == v {
+                data.push_str(&format!(
+                    "<NextVersionIdMarker>{}</NextVersionIdMarker>",
+                    version.version_id
+                ));
+            }
+        }
+    }
+
+    for (key, (is_latest, version)) in page.entries {
+        let common = format!(
+            "<Key>{}</Key><VersionId>{}</VersionId><IsLatest>{}</IsLatest><LastModified>{}</LastModified>",
+            encode(key, url_encoding),
+            version.version_id,
+            is_latest,
+            to_iso8601utc(version.last_modified)
+        );
+        match version.delete_marker {
+            true => data.push_str(&format!("<DeleteMarker>{}</DeleteMarker>", common)),
+            false => data.push_str(&format!(
+                "<Version>{}<ETag>\"{}\"</ETag><Size>{}</Size><StorageClass>STANDARD</StorageClass></Version>",
+                common,
+                version.etag,
+                version.data.len()
+            )),
+        }
+    }
+    for prefix in page.prefixes {
+        data.push_str(&format!(
+            "<CommonPrefixes><Prefix>{}</Prefix></CommonPrefixes>",
+            encode(prefix, url_encoding)
+        ));
+    }
+    data.push_str("</ListVersionsResult>");
+    Ok(xml(data))
+}
+
+fn parse_tagging(root: &Element) -> Result<BTreeMap<String, String>, S3Error> {
+    let tag_set = root.get_child("TagSet").ok_or(S3Error::malformed_xml())?;
+    let mut tags = BTreeMap::new();
+    for tag in tag_set.children.iter().filter_map(|v| v.as_element()) {
+        if tag.name == "Tag" {
+            tags.insert(get_default_text(tag, "Key"), get_default_text(tag, "Value"));
+        }
+    }
+    Ok(tags)
+}
+
+fn tagging_xml(tags: &BTreeMap<String, String>) -> String {
+    let mut data = format!("<Tagging xmlns=\"{}\"><TagSet>", XMLNS);
+    for (key, value) in tags.iter() {
+        data.push_str(&format!(
+            "<Tag><Key>{}</Key><Value>{}</Value></Tag>",
+            escape(key),
+            escape(value)
+        ));
+    }
+    data.push_str("</TagSet></Tagging>");
+    data
+}
+
+// Parses `bytes=start-end`, `bytes=start-` or `bytes=-suffix` into an inclusive range.
+fn parse_range(value: &str, size: usize) -> Result<(usize, usize), S3Error> {
+    let invalid = || {
+        S3Error::new(
+            416,
+            "InvalidRange",
+            "The requested range is not satisfiable",
+        )
+    };
+    let (start, end) = value
+        .strip_prefix("bytes=")
+        .and_then(|v| v.split_once('-'))
+        .ok_or_else(invalid)?;
+    let (start, end) = match (start.parse::<usize>(), end.parse::<usize>()) {
+        (Ok(s), Ok(e)) => (s, e.min(size.saturating_sub(1))),
+        (Ok(s), Err(_)) if end.is_empty() => (s, size.saturating_sub(1)),
+        (Err(_), Ok(n)) if start.is_empty() && n > 0 => (size.saturating_sub(n), size - 1),
+        _ => return Err(invalid()),
+    };
+    if start >= size || start > end {
+        return Err(invalid());
+    }
+    Ok((start, end))
+}
+
+fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|v| !v.is_empty())
+        .map(|v| {
+            let (key, value) = v.split_once('=').unwrap_or((v, ""));
+            let decode = |s: &str| {
+                urlencoding::decode(s)
+                    .map(|v| v.into_owned())
+                    .unwrap_or_else(|_| s.to_string())
+            };
+            (decode(key), decode(value))
+        })
+        .collect()
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn random_id() -> String {
+    format!("{:032x}", rand::random::<u128>())
+}
+
+fn precondition_failed() -> S3Error {
+    S3Error::new(
+        412,
+        "PreconditionFailed",
+        "At least one of the pre-conditions you specified did not hold",
+    )
+}
+
+fn empty(status: u16) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn xml(data: String) -> Response<Body> {
+    xml_with(Response::builder().status(200), data)
+}
+
+fn xml_with(builder: Builder, data: String) -> Response<Body> {
+    builder
+        .header("Content-Type", "application/xml")
+        .body(Body::from(data))
+        .unwrap()
+}
+
+fn error_response(err: S3Error, resource: &str) -> Response<Body> {
+    let (bucket, key) = resource
+        .trim_start_matches('/')
+        .split_once('/')
+        .unwrap_or((resource.trim_start_matches('/'), ""));
+    let request_id = random_id();
+    xml_with(
+        Response::builder()
+            .status(StatusCode::from_u16(err.status).unwrap())
+            .header("x-amz-request-id", &request_id),
+        format!(
+            "<Error><Code>{}</Code><Message>{}</Message><BucketName>{}</BucketName><Key>{}</Key><Resource>{}</Resource><RequestId>{}</RequestId><HostId></HostId></Error>",
+            err.code,
+            escape(err.message),
+            escape(bucket),
+            escape(key),
+            escape(resource),
+            request_id
+        ),
+    )
+}